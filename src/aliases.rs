@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use crate::names;
+
+/// Common names for templates whose upstream name is hard to guess.
+const BUILTIN_ALIASES: &[(&str, &str)] = &[
+    ("osx", "Global/macOS"),
+    ("mac", "Global/macOS"),
+    ("macos", "Global/macOS"),
+    ("windows", "Global/Windows"),
+    ("linux", "Global/Linux"),
+    ("jetbrains", "Global/JetBrains"),
+    ("idea", "Global/JetBrains"),
+    ("vscode", "Global/VisualStudioCode"),
+    ("vim", "Global/Vim"),
+    ("emacs", "Global/Emacs"),
+    ("js", "Node"),
+    ("javascript", "Node"),
+    ("nodejs", "Node"),
+    ("ts", "Node"),
+    ("typescript", "Node"),
+    ("golang", "Go"),
+    ("c++", "C++"),
+    ("cpp", "C++"),
+    ("py", "Python"),
+    ("python3", "Python"),
+    ("rb", "Ruby"),
+    ("csharp", "VisualStudio"),
+    ("c#", "VisualStudio"),
+    ("dotnet", "VisualStudio"),
+];
+
+/// Looks up `input` case-insensitively, preferring user aliases over built-in ones.
+pub fn resolve(input: &str, user: &BTreeMap<String, String>) -> Option<String> {
+    user.iter()
+        .map(|(alias, target)| (alias.as_str(), target.as_str()))
+        .chain(BUILTIN_ALIASES.iter().copied())
+        .find(|(alias, _)| names::eq_folded(alias, input))
+        .map(|(_, target)| target.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_builtin_and_user_aliases() {
+        let user: BTreeMap<String, String> = [("js".to_string(), "Custom/JS".to_string())].into();
+        assert_eq!(resolve("OSX", &BTreeMap::new()).as_deref(), Some("Global/macOS"));
+        assert_eq!(resolve("c++", &BTreeMap::new()).as_deref(), Some("C++"));
+        assert_eq!(resolve("js", &user).as_deref(), Some("Custom/JS"));
+        assert!(resolve("rust", &user).is_none());
+    }
+}
@@ -0,0 +1,32 @@
+use crate::names;
+
+/// Paths each template is expected to ignore, used by `--check-ignore` to confirm the
+/// written file still does after merging and reordering.
+const SAMPLES: &[(&str, &[&str])] = &[
+    ("Node", &["node_modules/left-pad/index.js", "npm-debug.log"]),
+    ("Rust", &["target/debug/app"]),
+    ("Python", &["__pycache__/app.cpython-312.pyc", "dist/app.tar.gz"]),
+    ("Go", &["app.test", "coverage.out"]),
+    ("Java", &["App.class", "app.jar"]),
+    ("Maven", &["target/classes/App.class"]),
+    ("Gradle", &[".gradle/8.5/fileHashes.bin", "build/libs/app.jar"]),
+    ("Ruby", &[".bundle/config", "app.gem"]),
+    ("C", &["main.o", "libapp.so"]),
+    ("C++", &["main.o", "app.exe"]),
+    ("Dart", &[".dart_tool/package_config.json"]),
+    ("Elixir", &["_build/dev/app", "deps/jason/mix.exs"]),
+    ("Haskell", &["dist-newstyle/cache/plan.json", ".stack-work/install"]),
+    ("Swift", &[".build/debug/app"]),
+    ("Terraform", &[".terraform/providers/registry", "terraform.tfstate"]),
+    ("Global/macOS", &[".DS_Store"]),
+    ("Global/JetBrains", &[".idea/workspace.xml"]),
+    ("Global/VisualStudioCode", &[".vscode/launch.json.bak", ".history/app.js"]),
+];
+
+/// Sample artifacts for `template`, empty for templates without any on record.
+pub fn samples(template: &str) -> &'static [&'static str] {
+    SAMPLES
+        .iter()
+        .find(|(name, _)| names::eq_folded(name, template))
+        .map_or(&[], |(_, paths)| *paths)
+}
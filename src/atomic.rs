@@ -0,0 +1,78 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::DynError;
+
+/// Replaces `path` with `contents` so readers see either the old file or the new one,
+/// never a partial write. Keeps the old file's permissions and writes through symlinks.
+pub fn write(path: &Path, contents: &[u8]) -> Result<(), DynError> {
+    let target = resolve_symlink(path);
+    let tmp = temp_path(&target)?;
+    let result = write_then_rename(&tmp, &target, contents);
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(|err| format!("Cannot write {}: {err}", path.display()).into())
+}
+
+fn write_then_rename(tmp: &Path, target: &Path, contents: &[u8]) -> io::Result<()> {
+    // `create_new` gets the usual umask-derived mode for files that don't exist yet.
+    let mut file = OpenOptions::new().write(true).create_new(true).open(tmp)?;
+    file.write_all(contents)?;
+    if let Ok(existing) = fs::metadata(target) {
+        file.set_permissions(existing.permissions())?;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::rename(tmp, target)
+}
+
+fn resolve_symlink(path: &Path) -> PathBuf {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// A sibling of `target`, so the final rename never crosses file systems.
+fn temp_path(target: &Path) -> Result<PathBuf, DynError> {
+    let name = target
+        .file_name()
+        .ok_or_else(|| format!("{} has no file name", target.display()))?;
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.subsec_nanos();
+    let tmp_name = format!(".{}.{}-{nanos}.tmp", name.to_string_lossy(), process::id());
+    Ok(target.with_file_name(tmp_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_contents_and_leaves_no_temp_files() {
+        let dir = std::env::temp_dir().join(format!("gi-atomic-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".gitignore");
+
+        write(&path, b"target/\n").unwrap();
+        write(&path, b"node_modules/\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "node_modules/\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+            write(&path, b"dist/\n").unwrap();
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
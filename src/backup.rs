@@ -0,0 +1,67 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::DynError;
+
+pub const DEFAULT_SUFFIX: &str = ".bak";
+/// Placeholder in a suffix replaced by the current UTC time, e.g. `.{ts}.bak`.
+const TIMESTAMP: &str = "{ts}";
+
+/// Copies `path` next to itself with `suffix` appended, returning the copy's path.
+pub fn create(path: &Path, suffix: &str) -> Result<PathBuf, DynError> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let target = backup_path(path, suffix, now);
+    fs::copy(path, &target).map_err(|err| {
+        format!("Cannot back up {} to {}: {err}", path.display(), target.display())
+    })?;
+    Ok(target)
+}
+
+fn backup_path(path: &Path, suffix: &str, now: u64) -> PathBuf {
+    let suffix = suffix.replace(TIMESTAMP, &utc_timestamp(now));
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `YYYYMMDDTHHMMSSZ` for a Unix timestamp.
+pub fn utc_timestamp(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days (Howard Hinnant), shifted so years start in March.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_backups_with_optional_timestamp() {
+        let path = Path::new("project/.gitignore");
+        assert_eq!(
+            backup_path(path, DEFAULT_SUFFIX, 0),
+            Path::new("project/.gitignore.bak")
+        );
+        assert_eq!(
+            backup_path(path, ".{ts}.bak", 1_709_251_199),
+            Path::new("project/.gitignore.20240229T235959Z.bak")
+        );
+        assert_eq!(utc_timestamp(0), "19700101T000000Z");
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Advisory lock file coordinating concurrent runs; not a cache entry itself.
+const LOCK_FILE: &str = ".lock";
+
+/// A held cache lock, released on drop (or when the process exits).
+#[derive(Debug)]
+pub struct Lock {
+    _file: File,
+}
+
+/// Waits for a shared lock on `dir`, for reading. Creates `dir` if needed.
+pub fn lock_shared(dir: &Path) -> io::Result<Lock> {
+    let file = lock_file(dir)?;
+    file.lock_shared()?;
+    Ok(Lock { _file: file })
+}
+
+/// Waits for an exclusive lock on `dir`, for writing. Creates `dir` if needed.
+pub fn lock_exclusive(dir: &Path) -> io::Result<Lock> {
+    let file = lock_file(dir)?;
+    file.lock()?;
+    Ok(Lock { _file: file })
+}
+
+fn lock_file(dir: &Path) -> io::Result<File> {
+    fs::create_dir_all(dir)?;
+    File::options().create(true).truncate(false).write(true).open(dir.join(LOCK_FILE))
+}
+
+/// A file in the cache directory, for `cache info` and `cache purge`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Relative to the cache directory.
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl Entry {
+    pub fn age(&self) -> Duration {
+        self.modified.elapsed().unwrap_or_default()
+    }
+}
+
+/// Every file under `dir`, sorted by path; empty if the cache doesn't exist yet.
+pub fn entries(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    if dir.exists() {
+        collect(dir, dir, &mut entries)?;
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+fn collect(root: &Path, dir: &Path, entries: &mut Vec<Entry>) -> io::Result<()> {
+    for item in fs::read_dir(dir)? {
+        let item = item?;
+        let meta = item.metadata()?;
+        if meta.is_dir() {
+            collect(root, &item.path(), entries)?;
+        } else if item.file_name() != LOCK_FILE {
+            entries.push(Entry {
+                path: item.path().strip_prefix(root).unwrap_or(&item.path()).to_path_buf(),
+                size: meta.len(),
+                modified: meta.modified()?,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Marks a cached file as just used. Reads bump the modification time, which is what
+/// `purge` and `evict` go by, so often-used templates outlive ones fetched once.
+pub fn touch(path: &Path) -> io::Result<()> {
+    File::options().append(true).open(path)?.set_modified(SystemTime::now())
+}
+
+/// Deletes files under `dir` last used at least `max_age` ago, then any directories
+/// that leaves empty. Returns what was deleted.
+pub fn purge(dir: &Path, max_age: Duration) -> io::Result<Vec<Entry>> {
+    let stale: Vec<Entry> = entries(dir)?.into_iter().filter(|e| e.age() >= max_age).collect();
+    remove(dir, &stale)?;
+    Ok(stale)
+}
+
+/// Deletes the least recently used files under `dir` until it holds at most `max_size`
+/// bytes, sparing `keep` (relative to `dir`, usually the file just written).
+pub fn evict(dir: &Path, max_size: u64, keep: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = entries(dir)?;
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+    entries.sort_by_key(|e| e.modified);
+    let mut evicted = Vec::new();
+    for entry in entries {
+        if total <= max_size {
+            break;
+        }
+        if entry.path != keep {
+            total -= entry.size;
+            evicted.push(entry);
+        }
+    }
+    remove(dir, &evicted)?;
+    Ok(evicted)
+}
+
+fn remove(dir: &Path, entries: &[Entry]) -> io::Result<()> {
+    for entry in entries {
+        let path = dir.join(&entry.path);
+        fs::remove_file(&path)?;
+        for parent in path.ancestors().skip(1).take_while(|p| *p != dir) {
+            // Fails (and stops) at the first directory still holding something.
+            if fs::remove_dir(parent).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses ages such as `90s`, `45m`, `12h`, `30d` or `2w` (`--older-than`).
+pub fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("expected a number followed by s, m, h, d or w, got `{value}`"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" | "" => 86_400,
+        "w" => 7 * 86_400,
+        other => return Err(format!("unknown unit `{other}` (use s, m, h, d or w)")),
+    };
+    let seconds = number.checked_mul(seconds).ok_or_else(|| format!("`{value}` is too long"))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Packs every cached file into a zstd-compressed tarball at `bundle` (`cache export`),
+/// for copying onto machines without network access. Returns the number of files.
+pub fn export(dir: &Path, bundle: &Path) -> io::Result<usize> {
+    let entries = entries(dir)?;
+    let encoder = zstd::Encoder::new(File::create(bundle)?, 0)?;
+    let mut archive = tar::Builder::new(encoder);
+    for entry in &entries {
+        archive.append_path_with_name(dir.join(&entry.path), &entry.path)?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(entries.len())
+}
+
+/// Unpacks a bundle made by `export` into `dir`, replacing files it also holds (`cache
+/// import`). Entries that would land outside `dir` are refused. Returns the number of files.
+pub fn import(dir: &Path, bundle: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(File::open(bundle)?)?);
+    archive.set_preserve_mtime(true);
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        if !entry.unpack_in(dir)? {
+            let message = format!("{} points outside the cache", path.display());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The files under `prefix` in a bundle made by `export`, keyed by their path in it,
+/// without unpacking it anywhere.
+pub fn read_bundle(bundle: &Path, prefix: &Path) -> io::Result<HashMap<PathBuf, String>> {
+    let mut archive = tar::Archive::new(zstd::Decoder::new(File::open(bundle)?)?);
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !entry.header().entry_type().is_file() || !path.starts_with(prefix) {
+            continue;
+        }
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        files.insert(path, content);
+    }
+    Ok(files)
+}
+
+/// Parses sizes such as `500K`, `50MiB` or `1G` (`--cache-max-size`); a bare number is bytes.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("expected a size such as 500K or 50M, got `{value}`"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        other => return Err(format!("unknown unit `{other}` (use K, M or G)")),
+    };
+    number.checked_mul(multiplier).ok_or_else(|| format!("`{value}` is too large"))
+}
+
+/// A rough age such as `5m`, `3h` or `12d`.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// A byte count in B, KiB or MiB.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1_024 => format!("{bytes} B"),
+        1_024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1_024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_ages_that_overflow() {
+        assert!(parse_age(&format!("{}w", u64::MAX / 86_400)).is_err());
+        assert_eq!(parse_age(&format!("{}s", u64::MAX)), Ok(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn rejects_sizes_that_overflow() {
+        assert!(parse_size(&format!("{}G", u64::MAX >> 20)).is_err());
+        assert_eq!(parse_size(&u64::MAX.to_string()), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn parses_ages() {
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3_600)));
+        assert_eq!(parse_age("30"), Ok(Duration::from_secs(30 * 86_400)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 86_400)));
+        assert!(parse_age("3y").is_err());
+        assert!(parse_age("d").is_err());
+        assert_eq!(format_age(Duration::from_secs(7_300)), "2h");
+        assert_eq!(parse_size("50MiB"), Ok(50 << 20));
+        assert_eq!(parse_size("500k"), Ok(500 << 10));
+        assert_eq!(parse_size("64"), Ok(64));
+        assert!(parse_size("5T").is_err());
+        assert_eq!(format_size(2_048), "2.0 KiB");
+    }
+
+    #[test]
+    fn purges_old_files_and_empty_directories() {
+        let dir = std::env::temp_dir().join(format!("gi-cache-purge-{}", std::process::id()));
+        let nested = dir.join("templates/github/gitignore/main");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("Rust.gitignore"), "target/\n").unwrap();
+        fs::write(dir.join("types.json"), "{}").unwrap();
+
+        assert!(purge(&dir, Duration::from_secs(3_600)).unwrap().is_empty());
+        let purged = purge(&dir, Duration::ZERO).unwrap();
+        let paths: Vec<_> = purged.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [PathBuf::from("templates/github/gitignore/main/Rust.gitignore"), "types.json".into()]
+        );
+        assert!(!dir.join("templates").exists());
+        assert!(dir.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn exclusive_lock_waits_for_readers() {
+        let dir = std::env::temp_dir().join(format!("gi-cache-lock-{}", std::process::id()));
+        let reader = lock_shared(&dir).unwrap();
+        let second_reader = lock_shared(&dir).unwrap();
+        let file = lock_file(&dir).unwrap();
+        assert!(file.try_lock().is_err());
+        drop((reader, second_reader));
+        assert!(file.try_lock().is_ok());
+        assert!(entries(&dir).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn round_trips_bundles() {
+        let base = std::env::temp_dir().join(format!("gi-cache-bundle-{}", std::process::id()));
+        let (from, to) = (base.join("from"), base.join("to"));
+        fs::create_dir_all(from.join("blobs/2f")).unwrap();
+        fs::write(from.join("blobs/2f/7896d1"), "target/\n").unwrap();
+        fs::write(from.join("types.json"), "{}").unwrap();
+        let _lock = lock_shared(&from).unwrap();
+
+        let bundle = base.join("bundle.tar.zst");
+        assert_eq!(export(&from, &bundle).unwrap(), 2);
+        assert_eq!(import(&to, &bundle).unwrap(), 2);
+        assert_eq!(fs::read_to_string(to.join("blobs/2f/7896d1")).unwrap(), "target/\n");
+        let listing = |dir: &Path| -> Vec<(PathBuf, u64)> {
+            entries(dir).unwrap().into_iter().map(|e| (e.path, e.size)).collect()
+        };
+        assert_eq!(listing(&to), listing(&from));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_files() {
+        let dir = std::env::temp_dir().join(format!("gi-cache-evict-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let hour_ago = SystemTime::now() - Duration::from_secs(3_600);
+        for (name, age) in [("Go", 3), ("Node", 1), ("Rust", 2)] {
+            let path = dir.join(format!("{name}.gitignore"));
+            fs::write(&path, "0123456789").unwrap();
+            let modified = hour_ago - Duration::from_secs(age * 60);
+            File::options().append(true).open(&path).unwrap().set_modified(modified).unwrap();
+        }
+        touch(&dir.join("Go.gitignore")).unwrap();
+
+        let evicted = evict(&dir, 20, Path::new("Node.gitignore")).unwrap();
+        let paths: Vec<_> = evicted.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, [PathBuf::from("Rust.gitignore")]);
+        let evicted = evict(&dir, 5, Path::new("Node.gitignore")).unwrap();
+        assert_eq!(evicted.len(), 1);
+        assert!(dir.join("Node.gitignore").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::DynError;
+
+/// Set for the background copy of `gi` that keeps the clipboard's contents alive.
+const HOLD_ENV: &str = "GI_HOLD_CLIPBOARD";
+/// What that copy prints once the text is on the clipboard; anything else is an error.
+const READY: &str = "ok";
+
+/// Puts `text` on the clipboard. On X11 and Wayland the contents vanish with the process
+/// that set them, so there a background copy of `gi` takes the text over and serves it
+/// until something else is copied.
+pub fn copy(text: &str) -> Result<(), DynError> {
+    if cfg!(target_os = "linux") {
+        return hand_over(text);
+    }
+    open()?.set_text(text).map_err(|err| format!("Cannot copy to the clipboard: {err}"))?;
+    Ok(())
+}
+
+/// Whether this process is the background copy started by [`copy`].
+pub fn is_holder() -> bool {
+    env::var_os(HOLD_ENV).is_some()
+}
+
+/// In the background copy: puts stdin on the clipboard, reports on stdout, then keeps
+/// serving the text until the clipboard is overwritten.
+pub fn hold() {
+    let mut text = String::new();
+    let copied = io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|err| format!("Cannot read the text to copy: {err}").into())
+        .and_then(|_| open())
+        .and_then(|mut clipboard| {
+            clipboard
+                .set_text(text.as_str())
+                .map_err(|err| format!("Cannot copy to the clipboard: {err}").into())
+                .map(|()| clipboard)
+        });
+    let mut stdout = io::stdout();
+    match copied {
+        Ok(clipboard) => {
+            let _ = writeln!(stdout, "{READY}").and_then(|()| stdout.flush());
+            serve(clipboard, text);
+        }
+        Err(err) => {
+            let _ = writeln!(stdout, "{err}");
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn serve(mut clipboard: arboard::Clipboard, text: String) {
+    use arboard::SetExtLinux;
+    let _ = clipboard.set().wait().text(text);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn serve(_clipboard: arboard::Clipboard, _text: String) {}
+
+/// Starts the background copy, feeds it `text` and waits until it holds the clipboard.
+fn hand_over(text: &str) -> Result<(), DynError> {
+    let exe = env::current_exe()?;
+    // Its stderr is closed so it never holds up whoever reads ours.
+    let mut child = Command::new(exe)
+        .env(HOLD_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Cannot start the clipboard helper: {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let mut reply = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        BufReader::new(stdout).read_line(&mut reply)?;
+    }
+    match reply.trim_end() {
+        READY => Ok(()),
+        "" => {
+            let _ = child.wait();
+            Err("The clipboard helper exited without copying".into())
+        }
+        err => {
+            let _ = child.wait();
+            Err(err.to_string().into())
+        }
+    }
+}
+
+fn open() -> Result<arboard::Clipboard, DynError> {
+    arboard::Clipboard::new().map_err(|err| format!("Cannot open the clipboard: {err}").into())
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Decides color for stdout and stderr once, before anything is printed.
+///
+/// `NO_COLOR` (non-empty) always wins, then `CLICOLOR_FORCE` (non-zero) forces color on,
+/// then `CLICOLOR=0` turns it off; otherwise color follows whether the stream is a terminal.
+pub fn init() {
+    let var = |name: &str| env::var(name).ok();
+    console::set_colors_enabled(wanted(io::stdout().is_terminal(), var));
+    console::set_colors_enabled_stderr(wanted(io::stderr().is_terminal(), var));
+}
+
+fn wanted(is_terminal: bool, var: impl Fn(&str) -> Option<String>) -> bool {
+    if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+        return true;
+    }
+    if var("CLICOLOR").is_some_and(|value| value == "0") {
+        return false;
+    }
+    is_terminal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_of<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn honors_color_conventions() {
+        assert!(wanted(true, env_of(&[])));
+        assert!(!wanted(false, env_of(&[])));
+        assert!(!wanted(true, env_of(&[("NO_COLOR", "1")])));
+        assert!(wanted(true, env_of(&[("NO_COLOR", "")])));
+        assert!(wanted(false, env_of(&[("CLICOLOR_FORCE", "1")])));
+        assert!(!wanted(false, env_of(&[("CLICOLOR_FORCE", "0")])));
+        assert!(!wanted(false, env_of(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")])));
+        assert!(!wanted(true, env_of(&[("CLICOLOR", "0")])));
+    }
+}
@@ -0,0 +1,340 @@
+use directories::ProjectDirs;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup;
+use crate::document::SectionOrder;
+use crate::lint::LintConfig;
+use crate::names;
+use crate::rules::TemplateRules;
+use crate::snippets::Snippet;
+use crate::source::Source;
+use crate::DynError;
+
+const CONFIG_FILE: &str = "config.toml";
+const SNIPPETS_DIR: &str = "snippets";
+const OVERRIDES_DIR: &str = "overrides";
+
+/// User settings read from `config.toml` in the platform config directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Repository to fetch templates from (`owner/repo[@ref]`).
+    pub source: Option<Source>,
+    /// Further sources, tried in order for templates the first one doesn't have.
+    pub sources: Vec<SourceEntry>,
+    /// GitHub Enterprise Server hosting `source` (e.g. `github.example.com`).
+    pub github_host: Option<String>,
+    /// Base URL of the GitHub API, when `github_host`'s `/api/v3` doesn't fit.
+    pub api_url: Option<String>,
+    /// Base URL for raw file downloads, when `github_host`'s `/raw` doesn't fit.
+    pub raw_url: Option<String>,
+    /// Default output path instead of `.gitignore`.
+    pub output: Option<PathBuf>,
+    /// Type list cache lifetime in minutes.
+    pub cache_ttl_minutes: Option<u64>,
+    /// Set to `false` to never look for new releases during normal runs.
+    pub update_check: Option<bool>,
+    /// Largest the download cache may grow (e.g. `"50M"`), evicting least recently used files.
+    pub cache_max_size: Option<String>,
+    /// Back up files before changing them: `true` for `.bak`, or a suffix such as `".{ts}.bak"`.
+    pub backup: Option<BackupSetting>,
+    /// Templates applied when none are given on the command line.
+    pub templates: Vec<String>,
+    /// Templates appended to every `get` run on top of the requested ones.
+    pub always: Vec<String>,
+    /// Named template sets invoked as `@name` (e.g. `fullstack = ["Node", "React"]`).
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// Inline snippets invoked as `+name`, shadowing files in the snippets directory.
+    pub snippets: BTreeMap<String, Snippet>,
+    pub detect: DetectConfig,
+    /// Extra name aliases (e.g. `k8s = "Kubernetes"`), checked before the built-in ones.
+    pub aliases: BTreeMap<String, String>,
+    /// Post-processing rules keyed by template name.
+    pub rules: BTreeMap<String, TemplateRules>,
+    /// Note each section's source, commit and fetch time under its header.
+    pub provenance: bool,
+    /// Order applied templates are written in: `"input"` (the default) or `"alphabetical"`.
+    pub order: Option<SectionOrder>,
+    /// Shell commands run after every write, given `GI_OUTPUT` and `GI_TEMPLATES`.
+    pub post_write: Vec<String>,
+    /// Which `lint` rules run.
+    pub lint: LintConfig,
+}
+
+/// An entry in `sources`: `"owner/repo[@ref]"` on the hosts `source` uses, or a table
+/// naming its own (`{ source = "github/gitignore", github_host = "github.com" }`), so a
+/// fork on GitHub Enterprise Server can fall back to github.com. Last, there may be a
+/// bundle made by `gi cache export` (`{ bundle = "/opt/gi/templates.tar.zst" }`) for
+/// templates no source has.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SourceEntry {
+    Plain(Source),
+    Hosted(HostedSource),
+    Bundled(BundledSource),
+}
+
+impl SourceEntry {
+    pub fn bundle(&self) -> Option<&Path> {
+        match self {
+            SourceEntry::Bundled(bundled) => Some(&bundled.bundle),
+            SourceEntry::Plain(_) | SourceEntry::Hosted(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HostedSource {
+    pub source: Source,
+    pub github_host: Option<String>,
+    pub api_url: Option<String>,
+    pub raw_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BundledSource {
+    pub bundle: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum BackupSetting {
+    Enabled(bool),
+    Suffix(String),
+}
+
+impl BackupSetting {
+    /// The suffix to back up with, or `None` when backups are off.
+    pub fn suffix(&self) -> Option<String> {
+        match self {
+            BackupSetting::Enabled(true) => Some(backup::DEFAULT_SUFFIX.to_string()),
+            BackupSetting::Enabled(false) => None,
+            BackupSetting::Suffix(suffix) => Some(suffix.clone()),
+        }
+    }
+}
+
+/// Extra detection rules, merged ahead of the built-in tables.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectConfig {
+    /// File name (or `*.ext` pattern) in the project root → template.
+    pub markers: BTreeMap<String, String>,
+    /// Source file extension → template.
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Loads `explicit` if given (which must exist), else the default file if present.
+    pub fn load(explicit: Option<&Path>) -> Result<Self, DynError> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let path = config_file_path()?;
+                if !path.exists() {
+                    return Ok(Self::default());
+                }
+                path
+            }
+        };
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("Cannot read {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("Invalid {}: {err}", path.display()).into())
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, DynError> {
+        let config: Config = toml::from_str(contents)?;
+        let bundle = config.sources.iter().position(|entry| entry.bundle().is_some());
+        if bundle.is_some_and(|index| index + 1 != config.sources.len()) {
+            return Err("a `bundle` entry must be the last of `sources`".into());
+        }
+        Ok(config)
+    }
+}
+
+/// Replaces every `@profile` in `types` with that profile's templates.
+pub fn expand_profiles(
+    types: Vec<String>,
+    profiles: &BTreeMap<String, Vec<String>>,
+) -> Result<Vec<String>, DynError> {
+    let mut expanded = Vec::with_capacity(types.len());
+    for item in types {
+        let Some(name) = item.strip_prefix('@') else {
+            expanded.push(item);
+            continue;
+        };
+        let members = profiles
+            .iter()
+            .find(|(profile, _)| names::eq_folded(profile, name))
+            .map(|(_, members)| members)
+            .ok_or_else(|| {
+                let known: Vec<String> = profiles.keys().map(|p| format!("@{p}")).collect();
+                if known.is_empty() {
+                    format!("Unknown profile @{name} (no profiles defined in config)")
+                } else {
+                    format!("Unknown profile @{name} (known: {})", known.join(", "))
+                }
+            })?;
+        expanded.extend(members.iter().cloned());
+    }
+    Ok(expanded)
+}
+
+/// Reads a `--from-file` list: one template (or `@profile`) per line; blank lines and
+/// `#` comments, whole-line or after a name, are skipped.
+pub fn read_template_list(path: &Path) -> Result<Vec<String>, DynError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Cannot read {}: {err}", path.display()))?;
+    Ok(parse_template_list(&contents))
+}
+
+fn parse_template_list(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split_once(" #").map_or(line, |(name, _)| name).trim())
+        .filter(|name| !name.is_empty() && !name.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn config_file_path() -> Result<PathBuf, DynError> {
+    let proj = ProjectDirs::from("dev", "gitignore-downloader", "gitignore-downloader")
+        .ok_or("Cannot determine config directory")?;
+    Ok(proj.config_dir().join(CONFIG_FILE))
+}
+
+/// `snippets/` next to the config file in effect.
+pub fn snippets_dir(explicit: Option<&Path>) -> Result<PathBuf, DynError> {
+    beside_config(explicit, SNIPPETS_DIR)
+}
+
+/// `overrides/` next to the config file: `Rust.gitignore` there replaces upstream `Rust`.
+pub fn overrides_dir(explicit: Option<&Path>) -> Result<PathBuf, DynError> {
+    beside_config(explicit, OVERRIDES_DIR)
+}
+
+fn beside_config(explicit: Option<&Path>, name: &str) -> Result<PathBuf, DynError> {
+    let config = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => config_file_path()?,
+    };
+    let parent = config.parent().unwrap_or(Path::new("."));
+    Ok(parent.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_detection_rules() {
+        let config = Config::parse(
+            r#"
+[detect.markers]
+"deno.json" = "Node"
+
+[detect.extensions]
+nim = "Nim"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.detect.markers["deno.json"], "Node");
+        assert_eq!(config.detect.extensions["nim"], "Nim");
+
+        assert!(Config::parse("").unwrap().detect.markers.is_empty());
+
+        let config = Config::parse("[rules.Node]\nremove = [\".env\"]").unwrap();
+        assert_eq!(config.rules["Node"].remove, vec![".env"]);
+        assert!(Config::parse("[unknown]\nkey = 1").is_err());
+
+        let config = Config::parse(
+            r#"
+source = "acme/gitignore@main"
+cache_ttl_minutes = 5
+templates = ["Rust"]
+always = ["Global/macOS"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.source.unwrap().owner, "acme");
+        assert_eq!(config.cache_ttl_minutes, Some(5));
+        assert_eq!(config.templates, vec!["Rust"]);
+        assert_eq!(config.always, vec!["Global/macOS"]);
+        assert!(config.backup.is_none());
+
+        let backup = |toml: &str| Config::parse(toml).unwrap().backup.unwrap().suffix();
+        assert_eq!(backup("backup = true").as_deref(), Some(".bak"));
+        assert_eq!(backup("backup = false"), None);
+        assert_eq!(backup("backup = \"~\"").as_deref(), Some("~"));
+        assert!(Config::parse("source = \"nope\"").is_err());
+    }
+
+    #[test]
+    fn parses_sources_with_their_own_hosts() {
+        let config = Config::parse(
+            r#"
+sources = ["acme/extra", { source = "github/gitignore", github_host = "github.com" }]
+"#,
+        )
+        .unwrap();
+        assert!(matches!(&config.sources[0], SourceEntry::Plain(source) if source.owner == "acme"));
+        let SourceEntry::Hosted(hosted) = &config.sources[1] else { panic!("not hosted") };
+        assert_eq!(hosted.source.owner, "github");
+        assert_eq!(hosted.github_host.as_deref(), Some("github.com"));
+        assert!(Config::parse("sources = [{ source = \"a/b\", host = \"x\" }]").is_err());
+    }
+
+    #[test]
+    fn parses_a_bundle_as_the_last_source() {
+        let config = Config::parse(r#"sources = ["a/b", { bundle = "/opt/gi.tar.zst" }]"#).unwrap();
+        assert_eq!(config.sources[1].bundle(), Some(Path::new("/opt/gi.tar.zst")));
+        assert_eq!(config.sources[0].bundle(), None);
+        assert!(Config::parse("sources = [{ bundle = \"gi.tar.zst\" }, \"a/b\"]").is_err());
+    }
+
+    #[test]
+    fn parses_plain_and_composed_snippets() {
+        let config = Config::parse(
+            r#"
+[snippets]
+env = ".env\n"
+
+[snippets.web]
+include = ["Node", "snippet:env"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.snippets["env"], Snippet::plain(".env\n"));
+        assert_eq!(
+            config.snippets["web"],
+            Snippet {
+                include: vec!["Node".to_string(), "snippet:env".to_string()],
+                body: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn expands_profiles() {
+        let config = Config::parse("[profiles]\nfullstack = [\"Node\", \"Global/macOS\"]").unwrap();
+        let types = vec!["rust".to_string(), "@FullStack".to_string()];
+        assert_eq!(
+            expand_profiles(types, &config.profiles).unwrap(),
+            vec!["rust", "Node", "Global/macOS"]
+        );
+
+        let err = expand_profiles(vec!["@web".to_string()], &config.profiles).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown profile @web (known: @fullstack)");
+    }
+
+    #[test]
+    fn reads_template_lists() {
+        let list = "# Our stack\nRust\n\n  Node  # frontend\n@editors\n#Go\n";
+        assert_eq!(parse_template_list(list), ["Rust", "Node", "@editors"]);
+    }
+}
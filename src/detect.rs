@@ -0,0 +1,407 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::config::DetectConfig;
+use crate::names;
+
+/// Build-tool manifests whose presence in the project root implies a template.
+/// Entries starting with `*.` match any root file with that extension.
+const MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "Node"),
+    ("deno.json", "Node"),
+    ("go.mod", "Go"),
+    ("pyproject.toml", "Python"),
+    ("setup.py", "Python"),
+    ("Pipfile", "Python"),
+    ("requirements.txt", "Python"),
+    ("Gemfile", "Ruby"),
+    ("composer.json", "Composer"),
+    ("pom.xml", "Maven"),
+    ("build.gradle", "Gradle"),
+    ("build.gradle.kts", "Gradle"),
+    ("settings.gradle", "Gradle"),
+    ("build.sbt", "Scala"),
+    ("project.clj", "Leiningen"),
+    ("mix.exs", "Elixir"),
+    ("rebar.config", "Erlang"),
+    ("pubspec.yaml", "Dart"),
+    ("Package.swift", "Swift"),
+    ("CMakeLists.txt", "CMake"),
+    ("stack.yaml", "Haskell"),
+    ("*.cabal", "Haskell"),
+    ("dune-project", "OCaml"),
+    ("elm.json", "Elm"),
+    ("Project.toml", "Julia"),
+    ("build.zig", "Zig"),
+    ("*.sln", "VisualStudio"),
+    ("*.csproj", "VisualStudio"),
+    ("*.tf", "Terraform"),
+];
+
+/// Source file extensions and the template they suggest, for trees without manifests.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("js", "Node"),
+    ("ts", "Node"),
+    ("go", "Go"),
+    ("py", "Python"),
+    ("rb", "Ruby"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("scala", "Scala"),
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("cc", "C++"),
+    ("cs", "VisualStudio"),
+    ("swift", "Swift"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("erl", "Erlang"),
+    ("hs", "Haskell"),
+    ("dart", "Dart"),
+    ("lua", "Lua"),
+    ("php", "Composer"),
+    ("r", "R"),
+    ("tex", "TeX"),
+    ("zig", "Zig"),
+    ("tf", "Terraform"),
+];
+
+/// Directories that hold dependencies or build output rather than project sources.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "build", "dist"];
+
+/// Upper bound on files inspected so huge trees stay fast.
+const MAX_SAMPLED_FILES: usize = 5_000;
+
+/// Most commonly requested templates, offered when nothing can be detected.
+pub const POPULAR_TEMPLATES: &[&str] = &[
+    "Node",
+    "Python",
+    "Java",
+    "Go",
+    "Rust",
+    "VisualStudio",
+    "Android",
+    "Ruby",
+    "C++",
+    "Global/macOS",
+];
+
+/// Returned when no template was requested and the run can't ask which: either the
+/// project gives no hints, or it does but nobody is at a terminal to confirm them.
+/// Carries suggestions so wrapping tools can offer a fallback choice.
+#[derive(Debug)]
+pub struct NothingDetected {
+    pub dir: PathBuf,
+    pub suggestions: Vec<String>,
+    /// Whether `suggestions` were detected in `dir` rather than popular picks.
+    pub detected: bool,
+}
+
+impl NothingDetected {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            suggestions: POPULAR_TEMPLATES.iter().map(|t| t.to_string()).collect(),
+            detected: false,
+        }
+    }
+
+    /// Templates detected in `dir` but not applied, since nobody confirmed them.
+    pub fn unconfirmed(dir: &Path, detections: Vec<Detection>) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            suggestions: detections.into_iter().map(|d| d.template).collect(),
+            detected: true,
+        }
+    }
+}
+
+impl fmt::Display for NothingDetected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.detected {
+            return write!(
+                f,
+                "No templates given; {} suggests {} (pass them, or --yes to apply them)",
+                self.dir.display(),
+                self.suggestions.join(", ")
+            );
+        }
+        write!(
+            f,
+            "No templates given and no project files recognized in {}; popular choices: {}",
+            self.dir.display(),
+            self.suggestions.join(", ")
+        )
+    }
+}
+
+impl Error for NothingDetected {}
+
+/// Marker and extension rules consulted by `detect`, in priority order.
+#[derive(Debug)]
+pub struct DetectionTable {
+    markers: Vec<(String, String)>,
+    extensions: Vec<(String, String)>,
+}
+
+impl DetectionTable {
+    pub fn builtin() -> Self {
+        let owned = |table: &[(&str, &str)]| {
+            table
+                .iter()
+                .map(|(key, template)| (key.to_string(), template.to_string()))
+                .collect()
+        };
+        Self {
+            markers: owned(MARKERS),
+            extensions: owned(EXTENSIONS),
+        }
+    }
+
+    /// Built-in rules with user-configured ones taking precedence.
+    pub fn with_config(config: &DetectConfig) -> Self {
+        let mut table = Self::builtin();
+        let user = |rules: &BTreeMap<String, String>| -> Vec<(String, String)> {
+            rules.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        };
+        table.markers.splice(0..0, user(&config.markers));
+        table.extensions.splice(0..0, user(&config.extensions));
+        table
+    }
+
+    fn template_for_extension(&self, ext: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+            .map(|(_, template)| template.as_str())
+    }
+}
+
+fn marker_present(dir: &Path, marker: &str) -> bool {
+    match marker.strip_prefix("*.") {
+        Some(ext) => fs::read_dir(dir).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry.path().is_file()
+                    && entry
+                        .path()
+                        .extension()
+                        .is_some_and(|found| found.eq_ignore_ascii_case(ext))
+            })
+        }),
+        None => dir.join(marker).is_file(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Detection {
+    pub template: String,
+    pub evidence: String,
+}
+
+/// Suggests templates for `dir`: manifest matches first, then source extensions.
+pub fn detect_templates(dir: &Path, table: &DetectionTable) -> Vec<Detection> {
+    let mut found = detect_markers(dir, table);
+
+    let mut by_template: Vec<(&str, usize, Vec<String>)> = Vec::new();
+    for (ext, count) in sample_extensions(dir) {
+        let Some(template) = table.template_for_extension(&ext) else {
+            continue;
+        };
+        match by_template.iter_mut().find(|(t, _, _)| names::eq_folded(t, template)) {
+            Some(entry) => {
+                entry.1 += count;
+                entry.2.push(ext);
+            }
+            None => by_template.push((template, count, vec![ext])),
+        }
+    }
+    by_template.sort_by_cached_key(|(template, count, _)| (Reverse(*count), names::fold(template)));
+    for (template, count, mut exts) in by_template {
+        if found.iter().any(|d| names::eq_folded(&d.template, template)) {
+            continue;
+        }
+        exts.sort();
+        let exts: Vec<String> = exts.iter().map(|ext| format!(".{ext}")).collect();
+        found.push(Detection {
+            template: template.to_string(),
+            evidence: format!("{count} {} file(s)", exts.join("/")),
+        });
+    }
+    found
+}
+
+/// Manifest-only detection, used where extension sampling would be too noisy.
+pub fn detect_markers(dir: &Path, table: &DetectionTable) -> Vec<Detection> {
+    let mut found: Vec<Detection> = Vec::new();
+    for (marker, template) in &table.markers {
+        let seen = found.iter().any(|d| names::eq_folded(&d.template, template));
+        if seen || !marker_present(dir, marker) {
+            continue;
+        }
+        found.push(Detection {
+            template: template.to_string(),
+            evidence: marker.to_string(),
+        });
+    }
+    found
+}
+
+/// Finds sub-projects below `root`, keyed by directory. Only immediate children
+/// are checked unless `recursive`, which walks the tree honoring ignore files.
+pub fn detect_subprojects(
+    root: &Path,
+    table: &DetectionTable,
+    recursive: bool,
+) -> Vec<(PathBuf, Vec<Detection>)> {
+    let mut walker = WalkBuilder::new(root);
+    walker.filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+        !SKIPPED_DIRS.contains(&name.as_ref())
+    });
+    if !recursive {
+        walker.max_depth(Some(1));
+    }
+    let mut dirs: Vec<PathBuf> = walker
+        .build()
+        .flatten()
+        // The root is skipped here rather than via `min_depth`, which would also
+        // skip loading the root's own ignore files.
+        .filter(|entry| entry.depth() > 0 && entry.file_type().is_some_and(|t| t.is_dir()))
+        .map(|entry| entry.into_path())
+        .collect();
+    dirs.sort();
+    dirs.into_iter()
+        .map(|dir| {
+            let detections = detect_markers(&dir, table);
+            (dir, detections)
+        })
+        .filter(|(_, detections)| !detections.is_empty())
+        .collect()
+}
+
+/// Counts lowercase file extensions under `dir`, skipping hidden and build directories.
+fn sample_extensions(dir: &Path) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    let mut sampled = 0;
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if let Some(ext) = path.extension() {
+                *counts.entry(ext.to_string_lossy().to_lowercase()).or_insert(0) += 1;
+            }
+            sampled += 1;
+            if sampled >= MAX_SAMPLED_FILES {
+                return counts;
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detects_templates_from_manifests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("Cargo.toml"), "").unwrap();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join("requirements.txt"), "").unwrap();
+        fs::write(dir.join("pyproject.toml"), "").unwrap();
+
+        let templates: Vec<String> = detect_templates(dir, &DetectionTable::builtin())
+            .into_iter()
+            .map(|d| d.template)
+            .collect();
+        assert_eq!(templates, vec!["Rust", "Node", "Python"]);
+    }
+
+    #[test]
+    fn detects_subprojects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("Cargo.toml"), "").unwrap();
+        fs::create_dir_all(dir.join("frontend")).unwrap();
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        fs::write(dir.join("frontend/package.json"), "{}").unwrap();
+        fs::create_dir_all(dir.join("services/api")).unwrap();
+        fs::write(dir.join("services/api/go.mod"), "").unwrap();
+
+        let subprojects = detect_subprojects(dir, &DetectionTable::builtin(), false);
+        assert_eq!(subprojects.len(), 1);
+        assert_eq!(subprojects[0].0, dir.join("frontend"));
+        assert_eq!(subprojects[0].1[0].template, "Node");
+    }
+
+    #[test]
+    fn detects_nested_subprojects_honoring_ignore_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("frontend")).unwrap();
+        fs::write(dir.join("frontend/package.json"), "{}").unwrap();
+        fs::create_dir_all(dir.join("services/api")).unwrap();
+        fs::create_dir_all(dir.join("services/legacy")).unwrap();
+        fs::write(dir.join("services/api/go.mod"), "").unwrap();
+        fs::write(dir.join("services/legacy/go.mod"), "").unwrap();
+        fs::write(dir.join(".ignore"), "services/legacy/\n").unwrap();
+
+        let dirs: Vec<PathBuf> = detect_subprojects(dir, &DetectionTable::builtin(), true)
+            .into_iter()
+            .map(|(dir, _)| dir)
+            .collect();
+        assert_eq!(dirs, vec![dir.join("frontend"), dir.join("services/api")]);
+    }
+
+    #[test]
+    fn detects_templates_from_extensions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/pkg")).unwrap();
+        fs::write(dir.join("scripts/a.py"), "").unwrap();
+        fs::write(dir.join("scripts/b.py"), "").unwrap();
+        fs::write(dir.join("main.go"), "").unwrap();
+        fs::write(dir.join("node_modules/pkg/index.js"), "").unwrap();
+
+        let detections = detect_templates(dir, &DetectionTable::builtin());
+        let templates: Vec<&str> = detections.iter().map(|d| d.template.as_str()).collect();
+        assert_eq!(templates, vec!["Python", "Go"]);
+        assert_eq!(detections[0].evidence, "2 .py file(s)");
+
+        let config = DetectConfig {
+            extensions: [("go".to_string(), "Custom".to_string())].into(),
+            ..DetectConfig::default()
+        };
+        let detections = detect_templates(dir, &DetectionTable::with_config(&config));
+        assert_eq!(detections[1].template, "Custom");
+    }
+}
@@ -0,0 +1,65 @@
+use console::style;
+use similar::{ChangeTag, TextDiff};
+
+/// Renders a unified diff from `old` to `new`, or `None` when they match.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let diff = TextDiff::from_lines(old, new);
+    Some(
+        diff.unified_diff()
+            .context_radius(3)
+            .header(old_label, new_label)
+            .to_string(),
+    )
+}
+
+/// Colors a unified diff for the stream it goes to: additions green, removals red.
+pub fn colorize_diff(diff: &str, for_stderr: bool) -> String {
+    let mut out = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let styled = if line.starts_with("+++") || line.starts_with("---") {
+            style(line).bold()
+        } else if line.starts_with('+') {
+            style(line).green()
+        } else if line.starts_with('-') {
+            style(line).red()
+        } else if line.starts_with("@@") {
+            style(line).cyan()
+        } else {
+            style(line)
+        };
+        let styled = if for_stderr { styled.for_stderr() } else { styled };
+        out.push_str(&styled.to_string());
+    }
+    out
+}
+
+/// Number of `old` lines that don't survive into `new`.
+pub fn removed_lines(old: &str, new: &str) -> usize {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .filter(|change| change.tag() == ChangeTag::Delete)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_unified_diff() {
+        assert!(unified_diff("a\n", "a\n", "old", "new").is_none());
+
+        let diff = unified_diff("target/\n", "target/\n*.rs.bk\n", "old", "new").unwrap();
+        assert!(diff.starts_with("--- old\n+++ new\n"));
+        assert!(diff.contains("+*.rs.bk\n"));
+    }
+
+    #[test]
+    fn counts_removed_lines() {
+        assert_eq!(removed_lines("a\nb\nc\n", "a\nc\nd\n"), 1);
+        assert_eq!(removed_lines("a\n", "a\n"), 0);
+    }
+}
@@ -0,0 +1,49 @@
+/// Rewrites gitignore patterns so Docker excludes the same paths (`--target dockerignore`).
+/// Docker matches every pattern from the build context root, so a bare `*.log` only
+/// covers the top level where git applies it at any depth, and `!` exceptions work inside
+/// excluded directories.
+pub fn translate(content: &str) -> String {
+    content.lines().map(|line| translate_line(line) + "\n").collect()
+}
+
+fn translate_line(line: &str) -> String {
+    let trimmed = line.trim_end();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return line.to_string();
+    }
+    let (negation, pattern) = match trimmed.strip_prefix('!') {
+        Some(pattern) => ("!", pattern),
+        None => ("", trimmed),
+    };
+    // `build/` means "directories only" to git. The slash stays for Docker, which drops it
+    // when matching but keeps that intent readable; only the slash placement is looked at.
+    let (pattern, dir_only) = match pattern.strip_suffix('/') {
+        Some(dir) => (dir.trim_end_matches('/'), "/"),
+        None => (pattern, ""),
+    };
+    if pattern.is_empty() {
+        return line.to_string();
+    }
+    let pattern = if pattern.contains('/') {
+        // Anchored in git too: a slash anywhere but the end ties it to the root.
+        pattern.trim_start_matches('/').to_string()
+    } else if pattern.starts_with("**") {
+        pattern.to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+    format!("{negation}{pattern}{dir_only}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_at_any_depth_like_git() {
+        let gitignore = "# Logs\n*.log\n/dist\nbuild/\ndocs/_build/\n**/tmp\n!keep.log\n\n";
+        let expected =
+            "# Logs\n**/*.log\ndist\n**/build/\ndocs/_build/\n**/tmp\n!**/keep.log\n\n";
+        assert_eq!(translate(gitignore), expected);
+    }
+}
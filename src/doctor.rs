@@ -0,0 +1,161 @@
+use console::style;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+use std::process;
+
+/// Proxy variables reqwest honors, in the order it checks them.
+const PROXY_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+];
+
+/// One line of the `doctor` report.
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Ok(String),
+    Skipped(String),
+    /// Something that will make runs fail, with what to do about it.
+    Problem { detail: String, hint: String },
+}
+
+impl Check {
+    pub fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Check { name, outcome: Outcome::Ok(detail.into()) }
+    }
+
+    pub fn skipped(name: &'static str, detail: impl Into<String>) -> Self {
+        Check { name, outcome: Outcome::Skipped(detail.into()) }
+    }
+
+    pub fn problem(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check { name, outcome: Outcome::Problem { detail: detail.into(), hint: hint.into() } }
+    }
+}
+
+/// Prints `checks` and returns how many found a problem.
+pub fn report(checks: &[Check]) -> usize {
+    let mut problems = 0;
+    for check in checks {
+        match &check.outcome {
+            Outcome::Ok(detail) => println!("{} {}: {detail}", style("ok").green(), check.name),
+            Outcome::Skipped(detail) => {
+                println!("{} {}: {detail}", style("--").dim(), check.name);
+            }
+            Outcome::Problem { detail, hint } => {
+                problems += 1;
+                println!("{} {}: {detail}", style("!!").red().bold(), check.name);
+                println!("   {} {hint}", style("hint:").yellow());
+            }
+        }
+    }
+    problems
+}
+
+/// Which proxy settings requests will go through.
+pub fn proxy(var: impl Fn(&str) -> Option<String>) -> Check {
+    let set: Vec<String> = PROXY_VARS
+        .iter()
+        .filter_map(|name| var(name).filter(|v| !v.is_empty()).map(|v| format!("{name}={v}")))
+        .collect();
+    if set.is_empty() {
+        Check::ok("proxy", "none (direct connections)")
+    } else {
+        Check::ok("proxy", set.join(", "))
+    }
+}
+
+/// Custom CA bundles, which must exist to be of any use.
+pub fn tls(var: impl Fn(&str) -> Option<String>) -> Check {
+    let mut set = Vec::new();
+    for name in ["SSL_CERT_FILE", "SSL_CERT_DIR"] {
+        let Some(value) = var(name).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        if !Path::new(&value).exists() {
+            let hint = format!("point {name} at your CA bundle, or unset it");
+            return Check::problem("tls", format!("{name}={value} does not exist"), hint);
+        }
+        set.push(format!("{name}={value}"));
+    }
+    if set.is_empty() {
+        Check::ok("tls", "system certificate store")
+    } else {
+        Check::ok("tls", set.join(", "))
+    }
+}
+
+/// Whether `path` can be written: its directory exists and accepts new files, and the
+/// file itself (if any) isn't read-only.
+pub fn writable(path: &Path) -> Check {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        let detail = format!("{} does not exist", dir.display());
+        return Check::problem("output", detail, "create it, or pass --parents");
+    }
+    if fs::metadata(path).is_ok_and(|meta| meta.permissions().readonly()) {
+        let detail = format!("{} is read-only", path.display());
+        return Check::problem("output", detail, "make it writable (chmod u+w)");
+    }
+    // Writes go through a temporary sibling and a rename, so that is what must work.
+    let probe = dir.join(format!(".gi-doctor-{}.tmp", process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Check::ok("output", format!("{} is writable", path.display()))
+        }
+        Err(err) => {
+            let detail = format!("cannot create files in {}: {err}", dir.display());
+            Check::problem("output", detail, "check the directory's owner and permissions")
+        }
+    }
+}
+
+/// What to do about a failed request to GitHub.
+pub fn http_hint(status: u16) -> &'static str {
+    match status {
+        401 => "GITHUB_TOKEN is set but rejected; refresh or unset it",
+        403 | 429 => "rate limited; set GITHUB_TOKEN to a personal access token",
+        404 => "check --source (OWNER/REPO[@REF]) and that the ref exists",
+        _ => "GitHub may be having trouble; try again shortly",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_proxy_and_tls_settings() {
+        let vars = |name: &str| match name {
+            "https_proxy" => Some("http://proxy:3128".to_string()),
+            "SSL_CERT_FILE" => Some("/nonexistent/ca.pem".to_string()),
+            _ => None,
+        };
+        assert_eq!(proxy(vars), Check::ok("proxy", "https_proxy=http://proxy:3128"));
+        assert!(matches!(tls(vars).outcome, Outcome::Problem { .. }));
+        assert_eq!(tls(|_| None), Check::ok("tls", "system certificate store"));
+    }
+
+    #[test]
+    fn checks_the_output_directory() {
+        let dir = std::env::temp_dir();
+        assert!(matches!(writable(&dir.join(".gitignore")).outcome, Outcome::Ok(_)));
+        let missing = dir.join("gi-doctor-missing/.gitignore");
+        assert!(matches!(writable(&missing).outcome, Outcome::Problem { .. }));
+    }
+}
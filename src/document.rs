@@ -0,0 +1,584 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{names, render_section, section_body, Template};
+
+/// A `.gitignore` split into the sections `gi` wrote (a `# --- Name ---` header up to
+/// a `# --- end Name ---` line, or in files written before end lines, to the next
+/// header) and the hand-written lines around them. Rendering a document that
+/// wasn't changed gives back exactly the text it was parsed from.
+#[derive(Debug)]
+pub struct Document {
+    blocks: Vec<Block>,
+    /// Whether the last line ends with a newline; true for an empty file.
+    trailing_newline: bool,
+}
+
+/// Where new sections go in an existing file (`--before`, `--after`, `--at-top`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Anchor {
+    #[default]
+    End,
+    Top,
+    /// Before the section of that name, or the line with that text.
+    Before(String),
+    After(String),
+}
+
+/// The order applied templates are written in (`--order`, `order` in the config).
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionOrder {
+    /// As given on the command line or in the config.
+    #[default]
+    Input,
+    /// By name, case-insensitively.
+    Alphabetical,
+}
+
+#[derive(Debug)]
+enum Block {
+    Unmanaged(Vec<String>),
+    Section(Section),
+}
+
+#[derive(Debug)]
+pub struct Section {
+    pub name: String,
+    header: String,
+    /// Comments right under the header that aren't template content: the options that
+    /// rewrote it and `--provenance` notes, which change on every fetch.
+    provenance: Vec<String>,
+    /// Everything up to the next header, trailing blank separator included.
+    lines: Vec<String>,
+    /// The `# --- end Name ---` line closing the section, so lines added after it stay
+    /// hand-written; `None` in files written before end lines.
+    footer: Option<String>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Document { blocks: Vec::new(), trailing_newline: true }
+    }
+}
+
+pub fn parse_section_header(line: &str) -> Option<&str> {
+    let name = line.trim_end_matches('\r').strip_prefix("# --- ")?.strip_suffix(" ---")?;
+    (!name.starts_with(END_PREFIX)).then_some(name)
+}
+
+const END_PREFIX: &str = "end ";
+
+/// The name in a `# --- end Name ---` line.
+pub fn parse_section_end(line: &str) -> Option<&str> {
+    let marker = line.trim_end_matches('\r').strip_prefix("# --- ")?.strip_suffix(" ---")?;
+    marker.strip_prefix(END_PREFIX)
+}
+
+fn section_end(name: &str) -> String {
+    format!("# --- {END_PREFIX}{name} ---")
+}
+
+/// Starts the line listing the options that rewrote a section (see `tidy::Transforms`).
+pub const WRITTEN_WITH: &str = "# Written with: ";
+
+pub fn is_provenance(line: &str) -> bool {
+    [WRITTEN_WITH, "# Source: ", "# Commit: ", "# Fetched: "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+impl Section {
+    /// The section's patterns, without provenance or trailing blank lines.
+    pub fn body(&self) -> String {
+        let lines: Vec<&str> =
+            self.lines.iter().map(|line| line.trim_end_matches('\r')).collect();
+        section_body(&lines.join("\n"))
+    }
+
+    /// The flags in the section's `# Written with:` line; empty without one.
+    pub fn written_with(&self) -> &str {
+        let line = self.provenance.iter().find_map(|line| line.strip_prefix(WRITTEN_WITH));
+        line.map_or("", |flags| flags.trim_end_matches('\r'))
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &String> {
+        let head = std::iter::once(&self.header).chain(&self.provenance);
+        head.chain(&self.lines).chain(&self.footer)
+    }
+
+    fn ends_with_blank(&self) -> bool {
+        let last = self.footer.as_ref().or(self.lines.last()).or(self.provenance.last());
+        last.is_some_and(|line| line.trim().is_empty())
+    }
+
+    /// Closes the section with an end line, so what follows stays hand-written.
+    fn close(&mut self) {
+        while self.lines.last().is_some_and(|line| line.trim().is_empty()) {
+            self.lines.pop();
+        }
+        self.footer = Some(section_end(&self.name));
+    }
+}
+
+impl Block {
+    fn lines(&self) -> Box<dyn Iterator<Item = &String> + '_> {
+        match self {
+            Block::Unmanaged(lines) => Box::new(lines.iter()),
+            Block::Section(section) => Box::new(section.lines()),
+        }
+    }
+
+    fn ends_with_blank(&self) -> bool {
+        match self {
+            Block::Unmanaged(lines) => lines.last().is_some_and(|line| line.trim().is_empty()),
+            Block::Section(section) => section.ends_with_blank(),
+        }
+    }
+
+    fn starts_with_blank(&self) -> bool {
+        match self {
+            Block::Unmanaged(lines) => lines.first().is_some_and(|line| line.trim().is_empty()),
+            Block::Section(_) => false,
+        }
+    }
+
+    fn is_closed_section(&self) -> bool {
+        matches!(self, Block::Section(section) if section.footer.is_some())
+    }
+}
+
+impl Document {
+    pub fn parse(content: &str) -> Self {
+        let mut lines: Vec<&str> = content.split('\n').collect();
+        let trailing_newline = content.is_empty() || content.ends_with('\n');
+        if trailing_newline {
+            lines.pop();
+        }
+
+        let mut blocks = Vec::new();
+        let mut unmanaged = Vec::new();
+        let mut lines = lines.into_iter().peekable();
+        while let Some(line) = lines.next() {
+            let Some(name) = parse_section_header(line) else {
+                unmanaged.push(line.to_string());
+                continue;
+            };
+            if !unmanaged.is_empty() {
+                blocks.push(Block::Unmanaged(std::mem::take(&mut unmanaged)));
+            }
+            let mut section = Section {
+                name: name.to_string(),
+                header: line.to_string(),
+                provenance: Vec::new(),
+                lines: Vec::new(),
+                footer: None,
+            };
+            while let Some(line) = lines.next_if(|line| is_provenance(line)) {
+                section.provenance.push(line.to_string());
+            }
+            while let Some(line) = lines.next_if(|line| parse_section_header(line).is_none()) {
+                if parse_section_end(line).is_some_and(|end| names::eq_folded(end, name)) {
+                    section.footer = Some(line.to_string());
+                    break;
+                }
+                section.lines.push(line.to_string());
+            }
+            blocks.push(Block::Section(section));
+        }
+        if !unmanaged.is_empty() {
+            blocks.push(Block::Unmanaged(unmanaged));
+        }
+        Document { blocks, trailing_newline }
+    }
+
+    pub fn render(&self) -> String {
+        let lines: Vec<&str> =
+            self.blocks.iter().flat_map(Block::lines).map(String::as_str).collect();
+        let mut out = lines.join("\n");
+        if self.trailing_newline && !lines.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &Section> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Section(section) => Some(section),
+            Block::Unmanaged(_) => None,
+        })
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections().find(|section| names::eq_folded(&section.name, name))
+    }
+
+    /// Whether the hand-written part of the file already holds `content`, as when
+    /// someone pasted a template in before using `gi`.
+    pub fn has_unmanaged(&self, content: &str) -> bool {
+        self.blocks.iter().any(|block| match block {
+            Block::Unmanaged(lines) => format!("{}\n", lines.join("\n")).contains(content),
+            Block::Section(_) => false,
+        })
+    }
+
+    /// The block index new sections go at for `anchor`, or `None` if its marker isn't
+    /// in the file. A marker line in hand-written text splits that text around the new
+    /// sections; one inside a managed section stands for the whole section.
+    pub fn position(&mut self, anchor: &Anchor) -> Option<usize> {
+        let (marker, after) = match anchor {
+            Anchor::End => return Some(self.blocks.len()),
+            Anchor::Top => return Some(0),
+            Anchor::Before(marker) => (marker.trim(), false),
+            Anchor::After(marker) => (marker.trim(), true),
+        };
+        let is_marker = |line: &String| line.trim() == marker;
+        let found = self.blocks.iter().enumerate().find_map(|(index, block)| match block {
+            Block::Section(section) => {
+                let matches = names::eq_folded(&section.name, marker)
+                    || section.lines.iter().any(is_marker);
+                matches.then_some((index, None))
+            }
+            Block::Unmanaged(lines) => {
+                let line = lines.iter().position(is_marker)?;
+                Some((index, Some(line + usize::from(after))))
+            }
+        })?;
+        match found {
+            (index, None) => Some(index + usize::from(after)),
+            (index, Some(0)) => Some(index),
+            (index, Some(split)) => {
+                if let Block::Unmanaged(lines) = &mut self.blocks[index]
+                    && split < lines.len()
+                {
+                    let rest = lines.split_off(split);
+                    self.blocks.insert(index + 1, Block::Unmanaged(rest));
+                }
+                Some(index + 1)
+            }
+        }
+    }
+
+    /// Inserts `tpl` as a section at block `index`, a blank line after what precedes it.
+    /// It gets an end line, so lines added below it later don't become part of it.
+    pub fn insert(&mut self, index: usize, tpl: &Template) {
+        let mut index = index;
+        if let Some(previous) = index.checked_sub(1).and_then(|i| self.blocks.get_mut(i))
+            && !previous.ends_with_blank()
+        {
+            match previous {
+                Block::Unmanaged(lines) => lines.push(String::new()),
+                Block::Section(section) if section.footer.is_none() => {
+                    section.lines.push(String::new())
+                }
+                Block::Section(_) => {
+                    self.blocks.insert(index, Block::Unmanaged(vec![String::new()]));
+                    index += 1;
+                }
+            }
+        }
+        if index == self.blocks.len() {
+            self.trailing_newline = true;
+        }
+        let mut blocks = Document::parse(&render_section(tpl)).blocks;
+        for block in &mut blocks {
+            if let Block::Section(section) = block {
+                section.close();
+            }
+        }
+        if self.blocks.get(index).is_some_and(|next| !next.starts_with_blank()) {
+            blocks.push(Block::Unmanaged(vec![String::new()]));
+        }
+        self.blocks.splice(index..index, blocks);
+    }
+
+    /// Adds `tpl` as the last section.
+    pub fn append(&mut self, tpl: &Template) {
+        self.insert(self.blocks.len(), tpl);
+    }
+
+    /// Swaps the content of `tpl`'s section for `tpl`'s, keeping its place; false if the
+    /// file has no such section.
+    pub fn replace(&mut self, tpl: &Template) -> bool {
+        let Some(section) = self.blocks.iter_mut().find_map(|block| match block {
+            Block::Section(section) if names::eq_folded(&section.name, &tpl.name) => Some(section),
+            _ => None,
+        }) else {
+            return false;
+        };
+        if let Some(Block::Section(fresh)) = Document::parse(&render_section(tpl)).blocks.pop() {
+            section.provenance = fresh.provenance;
+            section.lines = fresh.lines;
+            if section.footer.is_some() {
+                section.close();
+            }
+        }
+        true
+    }
+
+    /// Ends each section without an end line after the last of its lines found in what
+    /// `written` says `gi` wrote there, handing the lines below back as hand-written. In
+    /// files from before end lines, a section otherwise runs on to the next header and
+    /// takes the lines added after it along when it is replaced or dropped. Sections
+    /// `written` knows nothing about, or with none of its lines, are left as they are.
+    pub fn end_open_sections(&mut self, written: impl Fn(&Section) -> Option<String>) {
+        let mut index = 0;
+        while index < self.blocks.len() {
+            if let Block::Section(section) = &mut self.blocks[index]
+                && section.footer.is_none()
+                && let Some(content) = written(section)
+            {
+                let known: HashSet<&str> = content.lines().map(str::trim).collect();
+                let last = section.lines.iter().rposition(|line| {
+                    let line = line.trim();
+                    !line.is_empty() && known.contains(line)
+                });
+                if let Some(last) = last {
+                    let rest = section.lines.split_off(last + 1);
+                    let separator = rest.iter().take_while(|line| line.trim().is_empty());
+                    if separator.count() < rest.len() {
+                        self.blocks.insert(index + 1, Block::Unmanaged(rest));
+                        index += 1;
+                    } else {
+                        section.lines.extend(rest);
+                    }
+                }
+            }
+            index += 1;
+        }
+    }
+
+    /// Drops the sections `keep` rejects, returning their names.
+    pub fn retain(&mut self, keep: impl Fn(&Section) -> bool) -> Vec<String> {
+        let mut removed = Vec::new();
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut dropped = false;
+        for mut block in std::mem::take(&mut self.blocks) {
+            match &mut block {
+                Block::Section(section) if !keep(section) => {
+                    removed.push(section.name.clone());
+                    dropped = true;
+                    continue;
+                }
+                // The blank line that separated the section from what follows.
+                Block::Unmanaged(lines)
+                    if dropped
+                        && blocks.last().is_none_or(Block::ends_with_blank)
+                        && lines.first().is_some_and(|line| line.trim().is_empty()) =>
+                {
+                    lines.remove(0);
+                }
+                _ => {}
+            }
+            dropped = false;
+            if !matches!(&block, Block::Unmanaged(lines) if lines.is_empty()) {
+                blocks.push(block);
+            }
+        }
+        self.blocks = blocks;
+        removed
+    }
+
+    /// Rearranges the sections by `key` (ties keep their order). Hand-written blocks stay
+    /// where they are; sections take turns filling the places sections were in.
+    pub fn reorder<K: Ord>(&mut self, key: impl Fn(&Section) -> K) {
+        let mut slots = Vec::new();
+        let mut sections = Vec::new();
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            if matches!(block, Block::Section(_))
+                && let Block::Section(section) =
+                    std::mem::replace(block, Block::Unmanaged(Vec::new()))
+            {
+                slots.push(index);
+                sections.push(section);
+            }
+        }
+        sections.sort_by_cached_key(|section| key(section));
+        let last = self.blocks.len().saturating_sub(1);
+        let sections_at = slots.clone();
+        for (slot, mut section) in slots.into_iter().zip(sections) {
+            // A section without an end line can't go above hand-written lines.
+            if section.footer.is_some() || (slot != last && !sections_at.contains(&(slot + 1))) {
+                section.close();
+            } else if slot != last && !section.ends_with_blank() {
+                // The last section may have no blank line after it; anywhere else it needs one.
+                section.lines.push(String::new());
+            }
+            self.blocks[slot] = Block::Section(section);
+        }
+        let mut index = 1;
+        while index < self.blocks.len() {
+            if self.blocks[index - 1].is_closed_section()
+                && matches!(self.blocks[index], Block::Section(_))
+            {
+                self.blocks.insert(index, Block::Unmanaged(vec![String::new()]));
+            }
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str, content: &str) -> Template {
+        Template { name: name.into(), content: content.into(), provenance: None }
+    }
+
+    #[test]
+    fn round_trips_files_exactly() {
+        for content in [
+            "",
+            "\n",
+            "target/",
+            "*.log\r\n# --- Rust ---\r\ntarget/\r\n",
+            "# Mine\n.env\n\n# --- Rust ---\n# Source: x\ntarget/\n\n\n\
+             # --- Node ---\nnode_modules/",
+        ] {
+            assert_eq!(Document::parse(content).render(), content);
+        }
+        let crlf = Document::parse("# --- Rust ---\r\ntarget/\r\n\r\n");
+        assert_eq!(crlf.section("rust").unwrap().body(), "target/\n");
+    }
+
+    #[test]
+    fn separates_managed_and_unmanaged_lines() {
+        let document = Document::parse(
+            "# Mine\n.env\n\n# --- Rust ---\n# Source: x\n# Fetched: y\ntarget/\n\n\
+             # --- node ---\n",
+        );
+        let names: Vec<&str> = document.sections().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Rust", "node"]);
+        assert_eq!(document.section("Rust").unwrap().body(), "target/\n");
+        assert_eq!(document.section("Node").unwrap().body(), "");
+        assert!(document.has_unmanaged(".env\n"));
+        assert!(!document.has_unmanaged("target/\n"));
+    }
+
+    #[test]
+    fn appends_sections_a_blank_line_apart() {
+        let mut document = Document::parse("*.log");
+        for tpl in [template("Rust", "target/\n"), template("Node", "node_modules/")] {
+            let end = document.position(&Anchor::End).unwrap();
+            document.insert(end, &tpl);
+        }
+        assert_eq!(
+            document.render(),
+            "*.log\n\n# --- Rust ---\ntarget/\n# --- end Rust ---\n\n\
+             # --- Node ---\nnode_modules/\n# --- end Node ---\n"
+        );
+
+        let mut document = Document::default();
+        document.insert(0, &template("Rust", "target/\n"));
+        assert_eq!(document.render(), "# --- Rust ---\ntarget/\n# --- end Rust ---\n");
+    }
+
+    #[test]
+    fn inserts_at_markers() {
+        let content = "*.log\n!keep.log\n\n# --- Rust ---\ntarget/\n";
+        let inserted = |anchor: Anchor| {
+            let mut document = Document::parse(content);
+            let index = document.position(&anchor)?;
+            document.insert(index, &template("Node", "node_modules/\n"));
+            Some(document.render())
+        };
+        let node = "# --- Node ---\nnode_modules/\n# --- end Node ---\n";
+        assert_eq!(inserted(Anchor::Top).unwrap(), format!("{node}\n{content}"));
+        assert_eq!(
+            inserted(Anchor::Before("!keep.log".into())).unwrap(),
+            format!("*.log\n\n{node}\n!keep.log\n\n# --- Rust ---\ntarget/\n")
+        );
+        assert_eq!(
+            inserted(Anchor::After("*.log".into())).unwrap(),
+            format!("*.log\n\n{node}\n!keep.log\n\n# --- Rust ---\ntarget/\n")
+        );
+        assert_eq!(
+            inserted(Anchor::Before("rust".into())).unwrap(),
+            format!("*.log\n!keep.log\n\n{node}\n# --- Rust ---\ntarget/\n")
+        );
+        assert_eq!(
+            inserted(Anchor::After("target/".into())).unwrap(),
+            format!("{content}\n{node}")
+        );
+        assert_eq!(inserted(Anchor::After("missing".into())), None);
+    }
+
+    #[test]
+    fn reorders_sections_around_hand_written_lines() {
+        let mut document = Document::parse(
+            "# Mine\n.env\n\n# --- Node ---\nnode_modules/\n\n# --- Go ---\nvendor/\n\n\
+             # --- Rust ---\ntarget/",
+        );
+        document.reorder(|section| names::fold(&section.name));
+        assert_eq!(
+            document.render(),
+            "# Mine\n.env\n\n# --- Go ---\nvendor/\n\n# --- Node ---\nnode_modules/\n\n\
+             # --- Rust ---\ntarget/"
+        );
+
+        document.reorder(|section| section.name != "Rust");
+        let names: Vec<&str> = document.sections().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Rust", "Go", "Node"]);
+        assert!(document.render().starts_with("# Mine\n.env\n\n# --- Rust ---\ntarget/\n\n"));
+    }
+
+    #[test]
+    fn reorders_closed_sections_a_blank_line_apart() {
+        let mut document = Document::default();
+        for tpl in [template("Rust", "target/\n"), template("Go", "vendor/\n")] {
+            document.append(&tpl);
+        }
+        document.reorder(|section| names::fold(&section.name));
+        assert_eq!(
+            document.render(),
+            "# --- Go ---\nvendor/\n# --- end Go ---\n\n\
+             # --- Rust ---\ntarget/\n# --- end Rust ---\n"
+        );
+    }
+
+    #[test]
+    fn ends_open_sections_after_their_last_known_line() {
+        let content = "# --- Node ---\nnode_modules/\n\n.env\n\n# --- Go ---\n.env.local\n";
+        let mut document = Document::parse(content);
+        document.end_open_sections(|section| {
+            (section.name == "Node").then(|| "# Deps\nnode_modules/\n\n.env\n".into())
+        });
+        assert_eq!(document.section("Node").unwrap().body(), "node_modules/\n\n.env\n");
+
+        let mut document = Document::parse(content);
+        document.end_open_sections(|_| Some("node_modules/\n".into()));
+        assert_eq!(document.render(), content);
+        document.retain(|section| section.name != "Node");
+        assert_eq!(document.render(), ".env\n\n# --- Go ---\n.env.local\n");
+    }
+
+    #[test]
+    fn replaces_and_drops_sections_in_place() {
+        let content = "# Mine\n\n# --- Rust ---\nold/\n\n# --- Go ---\nvendor/\n\n*.tmp\n";
+        let mut document = Document::parse(content);
+        document.end_open_sections(|section| (section.name == "Go").then(|| "vendor/\n".into()));
+        assert_eq!(document.render(), content);
+        assert!(document.replace(&template("rust", "target/\n")));
+        assert!(!document.replace(&template("Node", "node_modules/\n")));
+        assert_eq!(document.retain(|section| section.name != "Go"), ["Go"]);
+        assert_eq!(document.render(), "# Mine\n\n# --- Rust ---\ntarget/\n\n*.tmp\n");
+    }
+
+    #[test]
+    fn end_lines_keep_hand_written_lines_out_of_sections() {
+        let content = "# --- Node ---\nold/\n# --- end Node ---\n\n!keep.js\n";
+        let mut document = Document::parse(content);
+        assert_eq!(document.render(), content);
+        assert_eq!(document.section("node").unwrap().body(), "old/\n");
+        assert!(document.replace(&template("Node", "node_modules/\n")));
+        assert_eq!(
+            document.render(),
+            "# --- Node ---\nnode_modules/\n# --- end Node ---\n\n!keep.js\n"
+        );
+        document.retain(|_| false);
+        assert_eq!(document.render(), "!keep.js\n");
+    }
+}
@@ -0,0 +1,46 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::pager::shell_command;
+use crate::DynError;
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `vi` (`notepad` on Windows).
+fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Opens `text` in the user's editor, as `git commit` does, and returns what was saved.
+/// The file is named like `output` so editors pick the right highlighting. Fails unless
+/// the editor exits successfully.
+pub fn edit(text: &str, output: &Path) -> Result<String, DynError> {
+    // A fresh private directory, removed on drop, so nobody can plant the file first.
+    let dir = tempfile::Builder::new().prefix("gi-edit-").tempdir()?;
+    let file_name = output.file_name().unwrap_or(".gitignore".as_ref());
+    let path = dir.path().join(file_name);
+    fs::write(&path, text)?;
+    run_editor(&path)?;
+    Ok(fs::read_to_string(&path)?)
+}
+
+fn run_editor(path: &Path) -> Result<(), DynError> {
+    let editor = editor();
+    let mut command = if cfg!(windows) {
+        shell_command(&format!("{editor} \"{}\"", path.display()))
+    } else {
+        // `$EDITOR` may carry arguments (`code --wait`), so let the shell split it.
+        let mut command = shell_command(&format!("{editor} \"$@\""));
+        command.arg(&editor).arg(path);
+        command
+    };
+    let status =
+        command.status().map_err(|err| format!("Cannot start editor '{editor}': {err}"))?;
+    if !status.success() {
+        return Err(format!("Editor '{editor}' exited with {status}").into());
+    }
+    Ok(())
+}
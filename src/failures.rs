@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::DynError;
+
+/// Errors collected across a run so one bad template or target doesn't hide the rest.
+#[derive(Debug, Default)]
+pub struct Failures {
+    items: Vec<(String, DynError)>,
+}
+
+impl Failures {
+    pub fn push(&mut self, context: impl Into<String>, err: DynError) {
+        self.items.push((context.into(), err));
+    }
+
+    /// Runs `f`, recording its error under `context` and returning `None` on failure.
+    pub fn capture<T>(
+        &mut self,
+        context: impl Into<String>,
+        f: impl FnOnce() -> Result<T, DynError>,
+    ) -> Option<T> {
+        match f() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(context, err);
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), DynError> {
+        if self.is_empty() { Ok(()) } else { Err(self.into()) }
+    }
+}
+
+impl fmt::Display for Failures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.items.as_slice() {
+            [(context, err)] => write!(f, "{context}: {err}"),
+            items => {
+                write!(f, "{} operations failed:", items.len())?;
+                for (context, err) in items {
+                    write!(f, "\n  {context}: {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for Failures {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_failure() {
+        let mut failures = Failures::default();
+        assert_eq!(failures.capture("Rust", || Ok(1)), Some(1));
+        assert!(failures.capture::<()>("Pyhton", || Err("not found".into())).is_none());
+        failures.push("Nod", "not found".into());
+
+        let err = failures.into_result().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2 operations failed:\n  Pyhton: not found\n  Nod: not found"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Returns true when the binary was invoked through a `gibo` symlink or alias.
+pub fn invoked_as_gibo(argv0: &OsString) -> bool {
+    Path::new(argv0)
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("gibo"))
+}
+
+/// Rewrites gibo's commands onto our CLI; `dump` and `search` exist natively.
+pub fn translate_args(mut args: Vec<OsString>) -> Vec<OsString> {
+    if args.len() < 2 {
+        args.push("--help".into());
+        return args;
+    }
+    let replacement: &[&str] = match args[1].to_str() {
+        Some("list") => &["--list"],
+        Some("update") => &["--list", "--no-cache"],
+        Some("version") => &["--version"],
+        Some("help") => &["--help"],
+        _ => return args,
+    };
+    args.splice(1..2, replacement.iter().map(OsString::from));
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(items: &[&str]) -> Vec<OsString> {
+        items.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn translates_gibo_commands() {
+        assert!(invoked_as_gibo(&"/usr/local/bin/gibo".into()));
+        assert!(!invoked_as_gibo(&"/usr/local/bin/gi".into()));
+
+        assert_eq!(translate_args(args(&["gibo", "list"])), args(&["gibo", "--list"]));
+        assert_eq!(
+            translate_args(args(&["gibo", "dump", "Rust"])),
+            args(&["gibo", "dump", "Rust"])
+        );
+        assert_eq!(translate_args(args(&["gibo"])), args(&["gibo", "--help"]));
+    }
+}
@@ -0,0 +1,152 @@
+use directories::BaseDirs;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::DynError;
+
+/// The excludes file git itself would use: `core.excludesFile` from any config scope
+/// (including `include`s and `~` expansion), otherwise `$XDG_CONFIG_HOME/git/ignore`
+/// (`~/.config/git/ignore`).
+pub fn global_excludes_file() -> Result<PathBuf, DynError> {
+    let configured = git(Path::new("."), &["config", "--path", "--get", "core.excludesFile"]);
+    if let Ok(Some(value)) = configured {
+        return Ok(PathBuf::from(value));
+    }
+    let config_home = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => home_dir()?.join(".config"),
+    };
+    Ok(config_home.join("git").join("ignore"))
+}
+
+pub fn home_dir() -> Result<PathBuf, DynError> {
+    Ok(BaseDirs::new()
+        .ok_or("Cannot determine home directory")?
+        .home_dir()
+        .to_path_buf())
+}
+
+/// `init.templateDir`, the directory `git init` copies into every new `.git`.
+pub fn template_dir() -> Option<PathBuf> {
+    let configured = git(Path::new("."), &["config", "--path", "--get", "init.templateDir"]);
+    configured.ok().flatten().map(PathBuf::from)
+}
+
+/// Sets `key` in the user's global git config.
+pub fn set_global_config(key: &str, value: &str) -> Result<(), DynError> {
+    let status = Command::new("git")
+        .args(["config", "--global", key, value])
+        .status()
+        .map_err(|err| format!("Cannot run git: {err}"))?;
+    if !status.success() {
+        return Err(format!("git config --global {key} failed").into());
+    }
+    Ok(())
+}
+
+/// The work tree containing `start`, as `git rev-parse --show-toplevel` reports it, so
+/// `GIT_DIR`/`GIT_WORK_TREE`, worktrees and submodules are honored. Without git installed,
+/// falls back to looking for `.git` in `start` and its parents.
+pub fn repo_root(start: &Path) -> Option<PathBuf> {
+    match git(start, &["rev-parse", "--show-toplevel"]) {
+        Ok(toplevel) => toplevel.map(PathBuf::from),
+        Err(_) => find_dot_git(start),
+    }
+}
+
+/// The hooks directory of the repository containing `start`, honoring `core.hooksPath`
+/// and worktrees.
+pub fn hooks_dir(start: &Path) -> Result<PathBuf, DynError> {
+    let hooks = git(start, &["rev-parse", "--git-path", "hooks"])
+        .map_err(|err| format!("Cannot run git: {err}"))?
+        .ok_or("Not inside a git repository")?;
+    Ok(start.join(hooks))
+}
+
+/// Untracked, not-ignored paths in the repository at `root`, relative to it. Untracked
+/// directories are listed once, with a trailing `/`, as `git status` shows them.
+pub fn untracked(root: &Path) -> Result<Vec<String>, DynError> {
+    let status = git(root, &["status", "--porcelain", "-z", "--untracked-files=normal"])
+        .map_err(|err| format!("Cannot run git: {err}"))?
+        .unwrap_or_default();
+    Ok(status
+        .split('\0')
+        .filter_map(|entry| entry.strip_prefix("?? "))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Which of `paths` the patterns in `excludes` alone would ignore, per `git check-ignore`
+/// in a scratch repository whose only ignore source is `excludes`, so the answer holds
+/// for any file name and isn't swayed by the ignore files around it.
+pub fn check_ignore(excludes: &Path, paths: &[&str]) -> Result<Vec<String>, DynError> {
+    let excludes = std::path::absolute(excludes)?;
+    let scratch = tempfile::TempDir::new()?;
+    // An empty template keeps `init.templateDir`'s info/exclude out of the scratch repo.
+    let init = Command::new("git")
+        .args(["init", "-q", "--template="])
+        .current_dir(scratch.path())
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .status()
+        .map_err(|err| format!("Cannot run git: {err}"))?;
+    if !init.success() {
+        return Err("git init failed".into());
+    }
+    let output = Command::new("git")
+        .arg("-c")
+        .arg(format!("core.excludesFile={}", excludes.display()))
+        .args(["check-ignore", "--no-index", "--"])
+        .args(paths)
+        .current_dir(scratch.path())
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .output()
+        .map_err(|err| format!("Cannot run git: {err}"))?;
+    // Exit status 1 just means none of the paths are ignored.
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git check-ignore failed: {}", stderr.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+}
+
+fn find_dot_git(start: &Path) -> Option<PathBuf> {
+    let start = std::path::absolute(start).ok()?;
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Runs git in `dir` and returns its trimmed stdout; `Ok(None)` when git fails or prints
+/// nothing, `Err` when git can't be run at all.
+fn git(dir: &Path, args: &[&str]) -> io::Result<Option<String>> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!value.is_empty()).then_some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_dot_git_in_parents() {
+        let root = env::temp_dir().join(format!("gi-git-test-{}", std::process::id()));
+        let nested = root.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(".git"), "gitdir: elsewhere\n").unwrap();
+
+        assert_eq!(find_dot_git(&nested), Some(root.clone()));
+        assert_eq!(find_dot_git(&root), Some(root.clone()));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,204 @@
+/// `.hgignore` rules rewritten as gitignore patterns, plus the rules that had no
+/// gitignore equivalent as `(line number, rule)`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Conversion {
+    pub patterns: Vec<String>,
+    pub untranslated: Vec<(usize, String)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Syntax {
+    /// Matched at any depth, like a gitignore pattern.
+    Glob,
+    /// Matched from the repository root.
+    RootGlob,
+    /// A literal path from the root.
+    Path,
+    /// Python regular expressions searched for anywhere in the path; Mercurial's default.
+    Regexp,
+}
+
+impl Syntax {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "glob" | "relglob" => Some(Syntax::Glob),
+            "rootglob" => Some(Syntax::RootGlob),
+            "path" | "relpath" => Some(Syntax::Path),
+            "re" | "regexp" | "relre" => Some(Syntax::Regexp),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a Mercurial `.hgignore`: `syntax:` switches and `glob:`/`re:`/... prefixes
+/// are honored, and regular expressions are translated when they amount to a glob.
+pub fn convert(content: &str) -> Conversion {
+    let mut conversion = Conversion::default();
+    let mut syntax = Syntax::Regexp;
+    for (index, line) in content.lines().enumerate() {
+        let rule = line.trim();
+        if rule.is_empty() || rule.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = rule.strip_prefix("syntax:") {
+            match Syntax::parse(name) {
+                Some(parsed) => syntax = parsed,
+                None => conversion.untranslated.push((index + 1, rule.to_string())),
+            }
+            continue;
+        }
+        let (rule_syntax, pattern) = match rule.split_once(':') {
+            Some((prefix, pattern)) => match Syntax::parse(prefix) {
+                Some(parsed) => (parsed, pattern),
+                // These pull in other files.
+                None if matches!(prefix, "include" | "subinclude") => {
+                    conversion.untranslated.push((index + 1, rule.to_string()));
+                    continue;
+                }
+                None => (syntax, rule),
+            },
+            None => (syntax, rule),
+        };
+        let converted = match rule_syntax {
+            Syntax::Glob => Some(expand_braces(pattern).into_iter().map(unrooted).collect()),
+            Syntax::RootGlob => Some(expand_braces(pattern).into_iter().map(rooted).collect()),
+            Syntax::Path => Some(vec![rooted(escape_glob(pattern))]),
+            Syntax::Regexp => regexp_to_glob(pattern).map(|pattern| vec![pattern]),
+        };
+        match converted {
+            Some(patterns) => conversion.patterns.extend(patterns),
+            None => conversion.untranslated.push((index + 1, rule.to_string())),
+        }
+    }
+    conversion
+}
+
+/// A glob Mercurial matches at any depth: gitignore does the same for patterns without a
+/// slash, and needs `**/` for the rest.
+fn unrooted(glob: String) -> String {
+    if !glob.trim_end_matches('/').contains('/') || glob.starts_with("**/") {
+        glob
+    } else {
+        format!("**/{glob}")
+    }
+}
+
+fn rooted(glob: String) -> String {
+    format!("/{}", glob.trim_start_matches('/'))
+}
+
+fn escape_glob(path: &str) -> String {
+    path.chars()
+        .flat_map(|c| match c {
+            '*' | '?' | '[' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Gitignore has no `{a,b}` alternatives, so each alternative becomes its own pattern.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (glob.find('{'), glob.find('}')) else {
+        return vec![glob.to_string()];
+    };
+    if close < open {
+        return vec![glob.to_string()];
+    }
+    let (head, tail) = (&glob[..open], &glob[close + 1..]);
+    glob[open + 1..close]
+        .split(',')
+        .flat_map(|alternative| expand_braces(&format!("{head}{alternative}{tail}")))
+        .collect()
+}
+
+/// The glob matching what `regexp` does, for the common shapes: literals, `.`, `.*`,
+/// `[^/]*`, character classes, `^`/`$` anchors and `(^|/)`/`(/|$)` component bounds.
+/// Anything else (alternation, groups, repetition) has no glob form.
+fn regexp_to_glob(regexp: &str) -> Option<String> {
+    // Without an anchor, a search may start mid-name.
+    let (rooted, bounded_start, rest) = if let Some(rest) = regexp.strip_prefix("(^|/)") {
+        (false, true, rest)
+    } else if let Some(rest) = regexp.strip_prefix('^') {
+        (true, true, rest)
+    } else {
+        (false, regexp.starts_with(".*"), regexp)
+    };
+    let (rest, open_end) = if let Some(rest) = rest.strip_suffix("(/|$)") {
+        (rest, false)
+    } else if let Some(rest) = rest.strip_suffix('$').filter(|rest| !rest.ends_with('\\')) {
+        (rest, false)
+    } else {
+        (rest, !rest.ends_with('/') && !rest.ends_with(".*"))
+    };
+
+    let mut glob = String::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                escaped if escaped.is_ascii_alphanumeric() => return None,
+                escaped @ ('*' | '?' | '[' | '\\') => glob.extend(['\\', escaped]),
+                escaped => glob.push(escaped),
+            },
+            '.' if chars.next_if_eq(&'*').is_some() => glob.push('*'),
+            '.' => glob.push('?'),
+            '[' => {
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                if class == "^/" && chars.next_if_eq(&'*').is_some() {
+                    glob.push('*');
+                } else {
+                    glob.push_str(&format!("[{}]", class.replacen('^', "!", 1)));
+                }
+            }
+            '(' | ')' | '|' | '+' | '?' | '*' | '{' | '}' | '^' | '$' => return None,
+            c => glob.push(c),
+        }
+    }
+    if glob.is_empty() {
+        return None;
+    }
+    if !bounded_start {
+        glob.insert(0, '*');
+    }
+    if open_end {
+        glob.push('*');
+    }
+    Some(if rooted { format!("/{glob}") } else { unrooted(glob) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(content: &str) -> Vec<String> {
+        convert(content).patterns
+    }
+
+    #[test]
+    fn converts_globs() {
+        let hgignore = "syntax: glob\n*.pyc\nbuild/out\n*.{o,so}\nrootglob:dist\npath:a*b\n";
+        let expected = ["*.pyc", "**/build/out", "*.o", "*.so", "/dist", "/a\\*b"];
+        assert_eq!(patterns(hgignore), expected);
+    }
+
+    #[test]
+    fn converts_glob_shaped_regexps() {
+        let hgignore = "\\.pyc$\n^build/\n(^|/)node_modules(/|$)\n^docs/_build$\n^tmp\n";
+        assert_eq!(
+            patterns(hgignore),
+            ["*.pyc", "/build/", "node_modules", "/docs/_build", "/tmp*"]
+        );
+        assert_eq!(patterns("re:^src/[^/]*\\.orig$"), ["/src/*.orig"]);
+    }
+
+    #[test]
+    fn lists_untranslatable_rules() {
+        let hgignore = "syntax: glob\n*.o\nre:^(foo|bar)$\ninclude:other\nre:\\d+\n";
+        let conversion = convert(hgignore);
+        assert_eq!(conversion.patterns, ["*.o"]);
+        let untranslated: Vec<usize> =
+            conversion.untranslated.iter().map(|(line, _)| *line).collect();
+        assert_eq!(untranslated, [3, 4, 5]);
+        assert_eq!(conversion.untranslated[0].1, "re:^(foo|bar)$");
+    }
+}
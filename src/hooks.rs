@@ -0,0 +1,86 @@
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::DynError;
+
+/// First comment line of hooks we write, so reinstalling can recognise them.
+const MARKER: &str = "# Installed by gitignore-downloader";
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum HookKind {
+    PreCommit,
+    PrePush,
+}
+
+impl HookKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+        }
+    }
+}
+
+/// A hook running `gi --check` on `output` (relative to the work tree, where git runs
+/// hooks). `gi` is looked up on `PATH` so upgrades and other machines sharing the hooks
+/// keep working; `exe` is only the fallback when it isn't there.
+pub fn script(exe: &Path, output: &Path) -> String {
+    let output = shell_quote(&output.to_string_lossy());
+    format!(
+        "#!/bin/sh\n{MARKER}: stops when ignore sections are missing or stale.\n\
+         if command -v gi >/dev/null 2>&1; then\n  \
+         exec gi --check --output {output}\n\
+         fi\n\
+         exec {} --check --output {output}\n",
+        shell_quote(&exe.to_string_lossy())
+    )
+}
+
+/// Writes `script` as the `kind` hook in `hooks_dir`. Someone else's hook is left alone
+/// unless `force`.
+pub fn install(
+    hooks_dir: &Path,
+    kind: HookKind,
+    script: &str,
+    force: bool,
+) -> Result<PathBuf, DynError> {
+    let path = hooks_dir.join(kind.file_name());
+    if let Ok(existing) = fs::read_to_string(&path)
+        && !existing.contains(MARKER)
+        && !force
+    {
+        let hint = "pass --force to replace it";
+        return Err(format!("{} already exists ({hint})", path.display()).into());
+    }
+    fs::create_dir_all(hooks_dir)?;
+    fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_paths_for_sh() {
+        let script = script(Path::new("/opt/it's/gi"), Path::new(".gitignore"));
+        assert!(script.ends_with("exec '/opt/it'\\''s/gi' --check --output '.gitignore'\n"));
+    }
+
+    #[test]
+    fn prefers_gi_on_the_path() {
+        let script = script(Path::new("/opt/gi"), Path::new(".gitignore"));
+        let on_path = script.find("  exec gi --check --output '.gitignore'\n").unwrap();
+        assert!(on_path < script.find("exec '/opt/gi'").unwrap());
+    }
+}
@@ -0,0 +1,193 @@
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{self, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic;
+use crate::cache;
+use crate::verify::sha256_hex;
+use crate::DynError;
+
+const JOURNAL_DIR: &str = "journal";
+const JOURNAL_FILE: &str = "journal.json";
+/// Older entries (and their saved copies) are dropped beyond this.
+const MAX_ENTRIES: usize = 20;
+
+/// One write made by `gi`, with enough to put the file back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub at: u64,
+    pub templates: Vec<String>,
+    /// The file before the write; `None` if it didn't exist.
+    pub before: Option<Snapshot>,
+    /// SHA-256 of what was written, to notice later hand edits.
+    pub after_sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub sha256: String,
+    /// Copy of the previous content, relative to the journal directory.
+    pub copy: PathBuf,
+}
+
+/// `journal/` under `data_dir`, or under the platform data directory.
+pub fn dir(data_dir: Option<&Path>) -> Result<PathBuf, DynError> {
+    let data_dir = match data_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => ProjectDirs::from("dev", "gitignore-downloader", "gitignore-downloader")
+            .ok_or("Cannot determine data directory")?
+            .data_local_dir()
+            .to_path_buf(),
+    };
+    Ok(data_dir.join(JOURNAL_DIR))
+}
+
+/// Records that `path` went from `before` (`None`: didn't exist) to `after`. Holds the
+/// journal's lock throughout, so concurrent runs don't lose each other's entries.
+pub fn record(
+    dir: &Path,
+    path: &Path,
+    before: Option<&str>,
+    after: &str,
+    templates: Vec<String>,
+) -> Result<(), DynError> {
+    let _lock = cache::lock_exclusive(dir)?;
+    let at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let before = match before {
+        Some(content) => {
+            let sha256 = sha256_hex(content.as_bytes());
+            let copy = unused_copy_name(dir, at, &sha256);
+            atomic::write(&dir.join(&copy), content.as_bytes())?;
+            Some(Snapshot { sha256, copy })
+        }
+        None => None,
+    };
+    let mut entries = load(dir)?;
+    entries.push(Entry {
+        path: path::absolute(path)?,
+        at,
+        templates,
+        before,
+        after_sha256: sha256_hex(after.as_bytes()),
+    });
+    let excess = entries.len().saturating_sub(MAX_ENTRIES);
+    for dropped in entries.drain(..excess) {
+        remove_copy(dir, &dropped);
+    }
+    save(dir, &entries)
+}
+
+/// Restores the file touched by the most recent entry and forgets that entry. Refuses if
+/// the file changed since, unless `force`.
+pub fn undo(dir: &Path, force: bool) -> Result<Entry, DynError> {
+    let _lock = cache::lock_exclusive(dir)?;
+    let mut entries = load(dir)?;
+    let entry = entries.pop().ok_or("Nothing to undo")?;
+    let current = fs::read(&entry.path).ok().map(|bytes| sha256_hex(&bytes));
+    if !force && current.as_deref() != Some(entry.after_sha256.as_str()) {
+        return Err(format!(
+            "{} changed since it was written; pass --force to restore it anyway",
+            entry.path.display()
+        )
+        .into());
+    }
+    match &entry.before {
+        Some(snapshot) => {
+            let previous = fs::read(dir.join(&snapshot.copy))
+                .map_err(|err| format!("Saved copy of {} is gone: {err}", entry.path.display()))?;
+            atomic::write(&entry.path, &previous)?;
+        }
+        None => match fs::remove_file(&entry.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        },
+    }
+    remove_copy(dir, &entry);
+    save(dir, &entries)?;
+    Ok(entry)
+}
+
+/// A name for the copy of content with `sha256` saved at `at` that no other entry's copy
+/// has, even one of the same content saved the same second.
+fn unused_copy_name(dir: &Path, at: u64, sha256: &str) -> PathBuf {
+    let mut counter = 0;
+    loop {
+        let copy = PathBuf::from(format!("{at}-{}-{counter}.before", &sha256[..12]));
+        if !dir.join(&copy).exists() {
+            return copy;
+        }
+        counter += 1;
+    }
+}
+
+fn remove_copy(dir: &Path, entry: &Entry) {
+    if let Some(snapshot) = &entry.before {
+        let _ = fs::remove_file(dir.join(&snapshot.copy));
+    }
+}
+
+fn load(dir: &Path) -> Result<Vec<Entry>, DynError> {
+    let path = dir.join(JOURNAL_FILE);
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("Corrupt journal {}: {err}", path.display()).into()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn save(dir: &Path, entries: &[Entry]) -> Result<(), DynError> {
+    atomic::write(&dir.join(JOURNAL_FILE), serde_json::to_string_pretty(entries)?.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undoes_writes_in_reverse_order() {
+        let root = std::env::temp_dir().join(format!("gi-journal-test-{}", std::process::id()));
+        let dir = root.join("journal");
+        let file = root.join(".gitignore");
+        fs::create_dir_all(&root).unwrap();
+
+        fs::write(&file, "a\n").unwrap();
+        record(&dir, &file, None, "a\n", vec!["A".into()]).unwrap();
+        fs::write(&file, "a\nb\n").unwrap();
+        record(&dir, &file, Some("a\n"), "a\nb\n", vec!["B".into()]).unwrap();
+
+        assert_eq!(undo(&dir, false).unwrap().templates, vec!["B"]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "a\n");
+
+        fs::write(&file, "edited\n").unwrap();
+        assert!(undo(&dir, false).unwrap_err().to_string().contains("--force"));
+        assert_eq!(undo(&dir, true).unwrap().templates, vec!["A"]);
+        assert!(!file.exists());
+        assert!(undo(&dir, false).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+    #[test]
+    fn keeps_a_copy_per_entry_for_identical_content() {
+        let root = std::env::temp_dir().join(format!("gi-journal-same-{}", std::process::id()));
+        let dir = root.join("journal");
+        let (first, second) = (root.join("a.gitignore"), root.join("b.gitignore"));
+        fs::create_dir_all(&root).unwrap();
+
+        // Same content in the same second: each entry still gets its own copy.
+        for file in [&first, &second] {
+            fs::write(file, "a\nb\n").unwrap();
+            record(&dir, file, Some("a\n"), "a\nb\n", vec!["B".into()]).unwrap();
+        }
+        assert_eq!(undo(&dir, false).unwrap().path, path::absolute(&second).unwrap());
+        assert_eq!(undo(&dir, false).unwrap().path, path::absolute(&first).unwrap());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "a\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "a\n");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
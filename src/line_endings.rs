@@ -0,0 +1,40 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// The line ending most of `text`'s lines use; `None` if it has no complete line.
+pub fn detect(text: &str) -> Option<LineEnding> {
+    let lines = text.matches('\n').count();
+    let crlf = text.matches("\r\n").count();
+    match lines {
+        0 => None,
+        _ if crlf * 2 > lines => Some(LineEnding::Crlf),
+        _ => Some(LineEnding::Lf),
+    }
+}
+
+/// `text` with every line ending, including stray ones from mixed files, made `ending`.
+pub fn convert(text: &str, ending: LineEnding) -> String {
+    let unix = text.replace("\r\n", "\n");
+    match ending {
+        LineEnding::Lf => unix,
+        LineEnding::Crlf => unix.replace('\n', "\r\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_converts_line_endings() {
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("target/"), None);
+        assert_eq!(detect("a\r\nb\r\nc\n"), Some(LineEnding::Crlf));
+        assert_eq!(detect("a\r\nb\nc\n"), Some(LineEnding::Lf));
+        assert_eq!(convert("a\r\nb\nc", LineEnding::Crlf), "a\r\nb\r\nc");
+        assert_eq!(convert("a\r\nb\n", LineEnding::Lf), "a\nb\n");
+    }
+}
@@ -0,0 +1,335 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// The same pattern appears earlier.
+    Duplicate,
+    /// An earlier pattern already ignores everything this one would.
+    Shadowed,
+    /// The pattern isn't a valid glob and git will never match it.
+    InvalidGlob,
+    /// Unescaped trailing whitespace, which git silently drops.
+    TrailingSpace,
+    /// `name/` matches a directory at any depth; `/name/` is often what was meant.
+    UnanchoredDir,
+}
+
+impl Rule {
+    fn enabled_by_default(self) -> bool {
+        // Most templates ignore `build/`-style directories anywhere on purpose.
+        self != Rule::UnanchoredDir
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rule::Duplicate => "duplicate",
+            Rule::Shadowed => "shadowed",
+            Rule::InvalidGlob => "invalid-glob",
+            Rule::TrailingSpace => "trailing-space",
+            Rule::UnanchoredDir => "unanchored-dir",
+        })
+    }
+}
+
+/// Which lint rules run, configured under `[lint]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Rules to turn on that are off by default (`unanchored-dir`).
+    pub enable: Vec<Rule>,
+    /// Rules to turn off.
+    pub disable: Vec<Rule>,
+}
+
+impl LintConfig {
+    fn runs(&self, rule: Rule) -> bool {
+        !self.disable.contains(&rule) && (rule.enabled_by_default() || self.enable.contains(&rule))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// 1-based line number.
+    pub line: usize,
+    pub rule: Rule,
+    pub message: String,
+}
+
+/// Checks every pattern line of `content` against the enabled rules.
+pub fn lint(content: &str, config: &LintConfig) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut first_seen: HashMap<&str, usize> = HashMap::new();
+    let mut earlier: Vec<&str> = Vec::new();
+    for (idx, raw) in content.lines().enumerate() {
+        let line = idx + 1;
+        let mut report = |rule: Rule, message: String| {
+            if config.runs(rule) {
+                findings.push(Finding { line, rule, message });
+            }
+        };
+        let pattern = raw.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+        if pattern.len() < raw.len() && !pattern.ends_with('\\') {
+            report(
+                Rule::TrailingSpace,
+                format!("trailing whitespace after `{pattern}` is ignored; escape it as `\\ `"),
+            );
+        }
+        // git never matches an unclosed `[`, though the `ignore` crate reads it literally.
+        let mut checker = GitignoreBuilder::new("");
+        if let Err(err) = checker.allow_unclosed_class(false).add_line(None, pattern) {
+            report(Rule::InvalidGlob, format!("`{pattern}` is not a valid pattern: {err}"));
+            continue;
+        }
+        let negated = pattern.starts_with('!');
+        if negated {
+            // Repeats after a negation can be deliberate re-exclusions.
+            first_seen.clear();
+            if let Some(cover) = excluded_parent(&earlier, &pattern[1..]) {
+                let message = format!("`{pattern}` can never apply: `{cover}` excludes a parent");
+                report(Rule::Shadowed, message);
+            }
+        } else if let Some(first) = first_seen.get(pattern) {
+            report(Rule::Duplicate, format!("`{pattern}` already appears on line {first}"));
+        } else {
+            first_seen.insert(pattern, line);
+            if let Some(cover) = shadowing_rule(&earlier, pattern) {
+                report(Rule::Shadowed, format!("`{pattern}` is already ignored by `{cover}`"));
+            }
+        }
+        if !negated && is_unanchored_dir(pattern) {
+            report(
+                Rule::UnanchoredDir,
+                format!("`{pattern}` matches at any depth; use `/{pattern}` for the root only"),
+            );
+        }
+        earlier.push(pattern);
+    }
+    findings
+}
+
+/// Just the shadowed and never-applying lines of `content`, as shown with `--diff`.
+pub fn shadowed(content: &str) -> Vec<Finding> {
+    let mut findings = lint(content, &LintConfig::default());
+    findings.retain(|finding| finding.rule == Rule::Shadowed);
+    findings
+}
+
+/// Applies the safe fixes for `findings` (from [`lint`] on `content`): drops duplicate and
+/// shadowed lines and trims trailing whitespace. Other findings need a human.
+pub fn fix(content: &str, findings: &[Finding]) -> String {
+    let mut out = String::new();
+    for (idx, line) in content.lines().enumerate() {
+        let rules: Vec<Rule> =
+            findings.iter().filter(|f| f.line == idx + 1).map(|f| f.rule).collect();
+        if rules.iter().any(|rule| matches!(rule, Rule::Duplicate | Rule::Shadowed)) {
+            continue;
+        }
+        if rules.contains(&Rule::TrailingSpace) {
+            out.push_str(line.trim_end());
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A `!pattern` in one template and a pattern in another that ignores the same path, so
+/// which one wins depends on the order the templates are written in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegationConflict {
+    /// Template holding the negation, and the negation itself.
+    pub negating: String,
+    pub negation: String,
+    /// Template whose pattern ignores the re-included path.
+    pub ignoring: String,
+    pub pattern: String,
+    /// Whether the ignoring template comes later and so undoes the negation.
+    pub negation_loses: bool,
+}
+
+/// Finds negation conflicts between `templates`, given as `(name, content)` in write order.
+pub fn negation_conflicts(templates: &[(&str, &str)]) -> Vec<NegationConflict> {
+    let mut conflicts = Vec::new();
+    for (neg_idx, (negating, content)) in templates.iter().enumerate() {
+        for negation in content.lines().map(str::trim).filter(|line| line.starts_with('!')) {
+            let target = &negation[1..];
+            for (idx, (ignoring, other)) in templates.iter().enumerate() {
+                if idx == neg_idx {
+                    continue;
+                }
+                let cover = other
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with(['#', '!']))
+                    .find(|pattern| ignores(pattern, target));
+                if let Some(pattern) = cover {
+                    conflicts.push(NegationConflict {
+                        negating: negating.to_string(),
+                        negation: negation.to_string(),
+                        ignoring: ignoring.to_string(),
+                        pattern: pattern.to_string(),
+                        negation_loses: idx > neg_idx,
+                    });
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Write order that keeps every negation effective: templates with conflicting negations
+/// move after the rest, otherwise keeping the given order.
+pub fn resolution_order<'a>(names: &[&'a str], conflicts: &[NegationConflict]) -> Vec<&'a str> {
+    let negating = |name: &&str| conflicts.iter().any(|c| c.negating == *name);
+    let (last, first): (Vec<&str>, Vec<&str>) = names.iter().copied().partition(negating);
+    first.into_iter().chain(last).collect()
+}
+
+/// Whether `pattern` ignores the path `target` names (globs in `target` only match the same
+/// glob text).
+fn ignores(pattern: &str, target: &str) -> bool {
+    if pattern == target {
+        return true;
+    }
+    if target.contains(['*', '?', '[']) {
+        return false;
+    }
+    let is_dir = target.ends_with('/');
+    let path = target.trim_start_matches('/').trim_end_matches('/');
+    build(&[pattern]).is_some_and(|matcher| {
+        matcher.matched_path_or_any_parents(Path::new(path), is_dir).is_ignore()
+    })
+}
+
+/// The earlier pattern that makes `pattern` redundant, if any: it already ignores the
+/// path a literal pattern names, or the directory a glob such as `build/**/*.o` lives in.
+fn shadowing_rule(earlier: &[&str], pattern: &str) -> Option<String> {
+    if earlier.is_empty() || pattern.contains('\\') {
+        return None;
+    }
+    let Some(glob_at) = pattern.find(['*', '?', '[']) else {
+        let is_dir = pattern.ends_with('/');
+        return ignoring_rule(&build(earlier)?, literal_path(pattern), is_dir);
+    };
+    let dir = literal_path(&pattern[..pattern[..glob_at].rfind('/')?]);
+    if dir.is_empty() {
+        return None;
+    }
+    ignoring_rule(&build(earlier)?, dir, true)
+}
+
+/// For a negation's target, the earlier pattern excluding one of its parent directories;
+/// git never looks inside an excluded directory, so the negation can't take effect.
+fn excluded_parent(earlier: &[&str], target: &str) -> Option<String> {
+    if earlier.is_empty() {
+        return None;
+    }
+    let matcher = build(earlier)?;
+    let path = Path::new(literal_path(target));
+    path.ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .find_map(|dir| {
+            let matched = matcher.matched(dir, true);
+            matched.is_ignore().then(|| matched.inner().map(|glob| glob.original().to_string()))?
+        })
+}
+
+/// The rule ignoring `path` itself or one of its parents.
+fn ignoring_rule(matcher: &Gitignore, path: &str, is_dir: bool) -> Option<String> {
+    let matched = matcher.matched_path_or_any_parents(Path::new(path), is_dir);
+    matched.is_ignore().then(|| matched.inner().map(|glob| glob.original().to_string()))?
+}
+
+fn literal_path(pattern: &str) -> &str {
+    pattern.trim_start_matches('/').trim_end_matches('/')
+}
+
+fn build(patterns: &[&str]) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder.add_line(None, pattern).ok()?;
+    }
+    builder.build().ok()
+}
+
+fn is_unanchored_dir(pattern: &str) -> bool {
+    pattern
+        .strip_suffix('/')
+        .is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str, config: &LintConfig) -> Vec<(usize, Rule)> {
+        lint(content, config).iter().map(|f| (f.line, f.rule)).collect()
+    }
+
+    #[test]
+    fn reports_each_rule() {
+        let content = "# Build\nbuild/\nbuild/output/\n*.log \nfoo[\n*.log \n!keep.log\n*.log\n";
+        assert_eq!(
+            rules(content, &LintConfig::default()),
+            vec![
+                (3, Rule::Shadowed),
+                (4, Rule::TrailingSpace),
+                (5, Rule::InvalidGlob),
+                (6, Rule::TrailingSpace),
+                (6, Rule::Duplicate),
+            ]
+        );
+    }
+
+    #[test]
+    fn fixes_duplicates_shadowing_and_trailing_space() {
+        let content = "build/\nbuild/output/\n*.log \nfoo[\n*.log\n";
+        let findings = lint(content, &LintConfig::default());
+        assert_eq!(fix(content, &findings), "build/\n*.log\nfoo[\n");
+    }
+
+    #[test]
+    fn finds_negations_other_templates_undo() {
+        let templates = [("Python", "*.log\n"), ("Keep", "!important.log\n"), ("Go", "bin/\n")];
+        let conflicts = negation_conflicts(&templates);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].pattern, "*.log");
+        assert!(!conflicts[0].negation_loses);
+
+        let reordered = [("Keep", "!important.log\n"), ("Python", "*.log\n")];
+        let conflicts = negation_conflicts(&reordered);
+        assert!(conflicts[0].negation_loses);
+        assert_eq!(resolution_order(&["Keep", "Python"], &conflicts), vec!["Python", "Keep"]);
+    }
+
+    #[test]
+    fn finds_globs_and_negations_under_excluded_directories() {
+        let content = "build/\nbuild/**/*.o\n!build/keep.txt\nlogs/*\n!logs/keep\n*.tmp\n";
+        let lines: Vec<usize> = shadowed(content).iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+    }
+
+    #[test]
+    fn rules_can_be_toggled() {
+        let config = LintConfig {
+            enable: vec![Rule::UnanchoredDir],
+            disable: vec![Rule::Shadowed],
+        };
+        assert_eq!(
+            rules("build/\nbuild/output/\n/dist/\n", &config),
+            vec![(1, Rule::UnanchoredDir)]
+        );
+    }
+}
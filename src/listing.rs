@@ -0,0 +1,247 @@
+use clap::ValueEnum;
+use console::style;
+use regex::{Regex, RegexBuilder};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::names;
+use crate::snippets;
+
+/// Where a listed template comes from, by its place in the template repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Category {
+    /// Top-level templates for languages and frameworks.
+    Root,
+    /// `Global/`: editors, operating systems and other per-user tools.
+    Global,
+    /// `community/`: contributed templates for less common stacks.
+    Community,
+    /// Snippets from the config (`+name`).
+    Snippet,
+}
+
+impl Category {
+    pub fn of(name: &str) -> Self {
+        if name.starts_with(snippets::PREFIX) {
+            Category::Snippet
+        } else if name.starts_with("Global/") {
+            Category::Global
+        } else if name.starts_with("community/") {
+            Category::Community
+        } else {
+            Category::Root
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Root => "root",
+            Category::Global => "global",
+            Category::Community => "community",
+            Category::Snippet => "snippet",
+        }
+    }
+}
+
+/// A one-line summary of a template: its first comment that isn't blank or a separator
+/// (`# Generated by Cargo` → `Generated by Cargo`). Section headers don't count.
+pub fn description(content: &str) -> Option<String> {
+    content
+        .lines()
+        .filter(|line| crate::document::parse_section_header(line).is_none())
+        .filter_map(|line| line.trim().strip_prefix('#'))
+        .map(|comment| comment.trim_matches(|c: char| c == '#' || c.is_whitespace()))
+        .find(|text| text.chars().any(char::is_alphanumeric))
+        .map(str::to_string)
+}
+
+/// `--list` at a terminal: names under a header per category, then the local overrides
+/// (which shadow upstream templates of the same name), laid out in columns to fit `width`.
+/// Empty groups are left out.
+pub fn render_groups(names: &[String], overrides: &[String], width: usize) -> String {
+    let groups = [
+        (Category::Root, "Templates"),
+        (Category::Global, "Global"),
+        (Category::Community, "Community"),
+        (Category::Snippet, "Snippets"),
+    ];
+    let mut blocks: Vec<(&str, Vec<&String>)> = groups
+        .iter()
+        .map(|&(category, title)| {
+            (title, names.iter().filter(|name| Category::of(name) == category).collect())
+        })
+        .collect();
+    blocks.push(("Local overrides", overrides.iter().collect()));
+    let blocks: Vec<String> = blocks
+        .into_iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(title, names)| {
+            let header = style(format!("{title} ({})", names.len())).bold();
+            format!("{header}\n{}", columns(&names, INDENT, width))
+        })
+        .collect();
+    blocks.join("\n")
+}
+
+const INDENT: &str = "  ";
+const GAP: usize = 2;
+
+/// `names` filled down the columns like `ls` does, each row prefixed by `indent`, using as
+/// many columns as fit in `width`. Names too long for any layout get a row each.
+pub fn columns(names: &[&String], indent: &str, width: usize) -> String {
+    let lengths: Vec<usize> = names.iter().map(|name| name.chars().count()).collect();
+    let available = width.saturating_sub(indent.len());
+    let fits = |rows: usize| {
+        let widths: Vec<usize> =
+            lengths.chunks(rows).map(|column| column.iter().copied().max().unwrap_or(0)).collect();
+        let total = widths.iter().sum::<usize>() + GAP * widths.len().saturating_sub(1);
+        (total <= available).then_some(widths)
+    };
+    let (rows, widths) = (1..=names.len().max(1))
+        .find_map(|rows| fits(rows).map(|widths| (rows, widths)))
+        .unwrap_or_else(|| (names.len().max(1), vec![0]));
+    let mut out = String::new();
+    for row in 0..rows.min(names.len()) {
+        let mut line = indent.to_string();
+        for (column, width) in widths.iter().enumerate() {
+            if let Some(name) = names.get(column * rows + row) {
+                line.push_str(&format!("{name:<width$}{:GAP$}", ""));
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Templates with a local copy in `dir` (`Rust.gitignore`, `Global/macOS.gitignore`).
+pub fn override_names(dir: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    collect_overrides(dir, "", &mut names)?;
+    names::sort_and_dedup(&mut names, |name| name);
+    Ok(names)
+}
+
+fn collect_overrides(dir: &Path, prefix: &str, names: &mut Vec<String>) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Some(dir_name) = path.file_name().and_then(|name| name.to_str()) {
+                collect_overrides(&path, &format!("{prefix}{dir_name}/"), names)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "gitignore")
+            && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            names.push(format!("{prefix}{stem}"));
+        }
+    }
+    Ok(())
+}
+
+/// `--filter`: a case-insensitive substring, or a regular expression between slashes.
+#[derive(Clone, Debug)]
+pub enum NameFilter {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl FromStr for NameFilter {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            Some(pattern) => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(NameFilter::Regex)
+                .map_err(|err| format!("Invalid regex: {err}")),
+            None => Ok(NameFilter::Substring(names::fold(value))),
+        }
+    }
+}
+
+/// Narrows `--list` output and the picker to matching names.
+#[derive(Clone, Debug, Default)]
+pub struct ListFilter {
+    pub name: Option<NameFilter>,
+    pub category: Option<Category>,
+}
+
+impl ListFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        let name_matches = match &self.name {
+            None => true,
+            Some(NameFilter::Substring(text)) => names::fold(name).contains(text.as_str()),
+            Some(NameFilter::Regex(regex)) => regex.is_match(name),
+        };
+        name_matches && self.category.is_none_or(|category| Category::of(name) == category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categorizes_templates_by_directory() {
+        assert_eq!(Category::of("Rust"), Category::Root);
+        assert_eq!(Category::of("Global/macOS"), Category::Global);
+        assert_eq!(Category::of("community/Java/JBoss").as_str(), "community");
+        assert_eq!(Category::of("+macos"), Category::Snippet);
+    }
+
+    #[test]
+    fn describes_templates_by_their_first_comment() {
+        let rust = "# --- Rust ---\n\n#\n# Generated by Cargo\n# will have compiled files\n";
+        assert_eq!(description(rust).as_deref(), Some("Generated by Cargo"));
+        assert_eq!(description("### Nim ###\nnimcache/\n").as_deref(), Some("Nim"));
+        assert_eq!(description("#########\n*.o\n"), None);
+        assert_eq!(description("node_modules/\n"), None);
+    }
+
+    #[test]
+    fn lays_names_out_in_columns() {
+        let names: Vec<String> = ["Ada", "C", "CMake", "Go", "Haskell", "Nim", "Node"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let names: Vec<&String> = names.iter().collect();
+        assert_eq!(columns(&names, "", 19), "Ada    Haskell\nC      Nim\nCMake  Node\nGo\n");
+        assert_eq!(columns(&names, "  ", 30), "  Ada  CMake  Haskell  Node\n  C    Go     Nim\n");
+        assert_eq!(columns(&names[..2], "", 1), "Ada\nC\n");
+        assert_eq!(columns(&[], "", 80), "");
+    }
+
+    #[test]
+    fn groups_names_by_category() {
+        let names: Vec<String> = ["Node", "Rust", "community/Elixir/Phoenix", "+locks"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            console::strip_ansi_codes(&render_groups(&names, &["Rust".to_string()], 20)),
+            "Templates (2)\n  Node  Rust\n\nCommunity (1)\n  community/Elixir/Phoenix\n\n\
+             Snippets (1)\n  +locks\n\nLocal overrides (1)\n  Rust\n"
+        );
+    }
+
+    #[test]
+    fn filters_by_substring_regex_and_category() {
+        let filter = |name: &str, category| ListFilter {
+            name: Some(name.parse().unwrap()),
+            category,
+        };
+        assert!(filter("python", None).matches("community/Python/JupyterNotebooks"));
+        assert!(filter("PYTHON", Some(Category::Root)).matches("Python"));
+        assert!(!filter("python", Some(Category::Community)).matches("Python"));
+        assert!(filter("/^(c|go)$/", None).matches("Go"));
+        assert!(!filter("/^(c|go)$/", None).matches("Godot"));
+        assert!("/(/".parse::<NameFilter>().is_err());
+        assert!(ListFilter::default().matches("anything"));
+    }
+}
@@ -0,0 +1,81 @@
+use clap::ValueEnum;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+use crate::DynError;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::OFF,
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Picks the level: an explicit `--log-level` wins, then `-v`/`-vv`; a log file defaults to info.
+pub fn level(explicit: Option<LogLevel>, verbose: u8, to_file: bool) -> LevelFilter {
+    match (explicit, verbose) {
+        (Some(level), _) => level.into(),
+        (None, 0) if to_file => LevelFilter::INFO,
+        (None, 0) => LevelFilter::WARN,
+        (None, 1) => LevelFilter::INFO,
+        (None, _) => LevelFilter::DEBUG,
+    }
+}
+
+/// Sends log events to `file` (appending, with timestamps) or to stderr.
+pub fn init(level: LevelFilter, file: Option<&Path>) -> Result<(), DynError> {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+    let installed = match file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| format!("Cannot open log file {}: {err}", path.display()))?;
+            builder.with_ansi(false).with_writer(Mutex::new(file)).try_init()
+        }
+        None => {
+            builder
+                .without_time()
+                .with_ansi(console::colors_enabled_stderr())
+                .with_writer(io::stderr)
+                .try_init()
+        }
+    };
+    installed.map_err(|err| -> DynError { err })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_level_beats_verbosity() {
+        assert_eq!(level(None, 0, false), LevelFilter::WARN);
+        assert_eq!(level(None, 0, true), LevelFilter::INFO);
+        assert_eq!(level(None, 1, false), LevelFilter::INFO);
+        assert_eq!(level(None, 3, false), LevelFilter::DEBUG);
+        assert_eq!(level(Some(LogLevel::Error), 2, false), LevelFilter::ERROR);
+    }
+}
@@ -1,309 +1,3746 @@
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use console::style;
 use directories::ProjectDirs;
-use dialoguer::{theme::ColorfulTheme, FuzzySelect};
-use reqwest::blocking::Client;
+use dialoguer::{theme::ColorfulTheme, Confirm, Select};
+use reqwest::blocking::{Client, RequestBuilder, Response};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File, OpenOptions};
-use std::io::{self, Write};
+use std::cell::{OnceCell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
 use std::path::{Path, PathBuf};
+use std::slice;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
+
+// Declared first so its `status!`/`note!` macros are visible to the modules below.
+#[macro_use]
+mod verbosity;
+
+mod aliases;
+mod artifacts;
+mod atomic;
+mod backup;
+mod cache;
+mod clipboard;
+mod color;
+mod config;
+mod detect;
+mod diff;
+mod document;
+mod dockerignore;
+mod doctor;
+mod editor;
+mod failures;
+mod gibo;
+mod git;
+mod hgignore;
+mod hooks;
+mod journal;
+mod line_endings;
+mod lint;
+mod listing;
+mod logging;
+mod matching;
+mod names;
+mod pager;
+mod picker;
+mod progress;
+mod rules;
+mod snippets;
+mod source;
+mod svn;
+mod sync;
+mod tidy;
+mod update;
+mod verify;
+
+use config::{expand_profiles, overrides_dir, snippets_dir, Config, SourceEntry};
+use detect::{detect_subprojects, detect_templates, Detection, DetectionTable, NothingDetected};
+use diff::{colorize_diff, removed_lines, unified_diff};
+use document::{parse_section_end, parse_section_header, Anchor, Document, Section, SectionOrder};
+use failures::Failures;
+use lint::LintConfig;
+use hooks::HookKind;
+use line_endings::LineEnding;
+use listing::{Category, ListFilter, NameFilter};
+use logging::LogLevel;
+use matching::{Matcher, Verdict};
+use rules::TemplateRules;
+use snippets::{Snippet, Snippets};
+use source::{Hosts, Source};
+use tidy::Transforms;
+use verify::Manifest;
 
-const TYPES_URL: &str = "https://api.github.com/repos/github/gitignore/contents";
-const RAW_BASE_URL: &str = "https://raw.githubusercontent.com/github/gitignore/master/";
 const USER_AGENT: &str = concat!("gitignore-downloader/", env!("CARGO_PKG_VERSION"));
 const CACHE_FILE: &str = "types.json";
+const TEMPLATE_CACHE_DIR: &str = "templates";
+/// Template bodies keyed by git blob SHA, shared by every source and project.
+const BLOB_CACHE_DIR: &str = "blobs";
+/// Markers for templates upstream answered 404 for, so repeats fail fast.
+const MISSING_CACHE_DIR: &str = "missing";
+/// How long a 404 is trusted before asking upstream again.
+const MISSING_TTL: Duration = Duration::from_secs(10 * 60);
+const RELEASE_CHECK_FILE: &str = "release.json";
+const FIXTURE_TYPES_FILE: &str = "contents.json";
+const FIXTURE_RAW_DIR: &str = "raw";
+const FIXTURE_COMMITS_DIR: &str = "commits";
+const FIXTURE_HOSTS_DIR: &str = "hosts";
+const FIXTURE_RELEASE_FILE: &str = "release.json";
+/// Release assets, by name, in place of their download URLs.
+const FIXTURE_ASSETS_DIR: &str = "assets";
+const DEFAULT_OUTPUT: &str = ".gitignore";
+/// `--output` value that means stdout.
+const STDOUT: &str = "-";
+/// Template argument that means "read it from stdin".
+const STDIN: &str = "-";
+/// Section name for a template piped in as `-` without `--stdin NAME`.
+const STDIN_TEMPLATE: &str = "Stdin";
 
 type DynError = Box<dyn std::error::Error>;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about = "Fetch .gitignore templates from github/gitignore")]
+#[command(
+    author,
+    version,
+    about = "Fetch .gitignore templates from github/gitignore",
+    args_conflicts_with_subcommands = true
+)]
 struct Cli {
-    /// Template type(s) to fetch (e.g. rust, node). If omitted, a fuzzy picker opens.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Template type(s) or `@profile`s to fetch (e.g. rust, @web). If omitted, a picker opens.
     #[arg(value_name = "TYPE", num_args = 0..)]
     types: Vec<String>,
 
+    /// Also apply the templates listed in this file, one per line (`#` starts a comment).
+    /// Repeatable.
+    #[arg(long, value_name = "PATH", global = true)]
+    from_file: Vec<PathBuf>,
+
+    /// Read a template named NAME from stdin (same as `-` among the types, which is named
+    /// `stdin`).
+    #[arg(long, value_name = "NAME", global = true)]
+    stdin: Option<String>,
+
     /// List all available template types.
     #[arg(short, long, action = ArgAction::SetTrue)]
     list: bool,
 
-    /// Output path (defaults to .gitignore in the current directory).
-    #[arg(short, long, value_name = "PATH")]
-    output: Option<PathBuf>,
+    /// With --list, also show size, last-modified date, and blob SHA.
+    #[arg(long, action = ArgAction::SetTrue, requires = "list")]
+    long: bool,
+
+    /// With --list --long or --format, look up the last-modified dates that aren't cached
+    /// yet (one API call per template).
+    #[arg(long, action = ArgAction::SetTrue, requires = "list")]
+    dates: bool,
+
+    /// With --list, print one name per line instead of grouping them by category (the
+    /// default at a terminal).
+    #[arg(long, action = ArgAction::SetTrue, requires = "list")]
+    flat: bool,
+
+    /// With --list or the picker, only show names containing TEXT (case-insensitive), or
+    /// matching /REGEX/.
+    #[arg(long, value_name = "TEXT|/REGEX/")]
+    filter: Option<NameFilter>,
+
+    /// With --list or the picker, only show templates of this kind.
+    #[arg(long, value_enum)]
+    category: Option<Category>,
+
+    /// With --list, print the catalog (name, category, size, last-updated) as a table.
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "list", conflicts_with = "json")]
+    format: Option<ListFormat>,
+
+    /// Print `--list`, `search`, and `--dry-run` results as JSON.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    json: bool,
+
+    /// Never prompt: accept confirmations and use detection instead of the picker.
+    #[arg(
+        short = 'y',
+        long,
+        visible_alias = "non-interactive",
+        action = ArgAction::SetTrue,
+        global = true
+    )]
+    yes: bool,
+
+    /// Never page long `--dry-run`, `show` and `diff` output through $PAGER.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    no_pager: bool,
+
+    /// Only print errors and requested data, no progress messages.
+    #[arg(short, long, action = ArgAction::SetTrue, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Explain what happens: -v shows URLs and cache decisions, -vv also HTTP statuses.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Log level, overriding -v (default: warn, or info with --log-file).
+    #[arg(long, value_enum, value_name = "LEVEL", global = true)]
+    log_level: Option<LogLevel>,
+
+    /// Append logs to this file instead of stderr.
+    #[arg(long, value_name = "PATH", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Output path (defaults to .gitignore in the current directory; `-` for stdout).
+    /// Repeat to write the same templates to several files; a directory gets a .gitignore.
+    #[arg(short, long, value_name = "PATH", global = true)]
+    output: Vec<PathBuf>,
 
     /// Overwrite the output instead of appending.
-    #[arg(long, action = ArgAction::SetTrue)]
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
     overwrite: bool,
 
+    /// Open the result in $VISUAL/$EDITOR before saving; nothing is written unless the
+    /// editor exits successfully.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    edit: bool,
+
+    /// Copy the merged templates to the clipboard; the output is only written too when
+    /// --output (or --target) is given.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    clipboard: bool,
+
+    /// Insert new sections before the section named MARKER, or the line reading MARKER,
+    /// instead of at the end (negations only work below what they negate).
+    #[arg(
+        long,
+        value_name = "MARKER",
+        global = true,
+        conflicts_with_all = ["after", "at_top", "overwrite"]
+    )]
+    before: Option<String>,
+
+    /// Insert new sections after the section named MARKER, or the line reading MARKER.
+    #[arg(long, value_name = "MARKER", global = true, conflicts_with_all = ["at_top", "overwrite"])]
+    after: Option<String>,
+
+    /// Insert new sections at the top of the file.
+    #[arg(long, action = ArgAction::SetTrue, global = true, conflicts_with = "overwrite")]
+    at_top: bool,
+
+    /// Order applied templates are written in (and `reorder` arranges sections by).
+    #[arg(long, value_enum, value_name = "ORDER", global = true)]
+    order: Option<SectionOrder>,
+
+    /// Write Windows (CRLF) line endings; by default the existing file's are kept.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "lf", global = true)]
+    crlf: bool,
+
+    /// Write Unix (LF) line endings, converting the whole file.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    lf: bool,
+
     /// Print the template(s) instead of writing to disk.
-    #[arg(long, action = ArgAction::SetTrue)]
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
     dry_run: bool,
 
+    /// Copy the existing file to PATH+SUFFIX before changing it (default `.bak`;
+    /// `{ts}` in SUFFIX becomes a UTC timestamp).
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = backup::DEFAULT_SUFFIX,
+        global = true
+    )]
+    backup: Option<String>,
+
+    /// Don't back up, even if the config asks for it.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "backup", global = true)]
+    no_backup: bool,
+
+    /// Show a colored diff of the pending changes before writing (with --dry-run, only the diff).
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    diff: bool,
+
+    /// Write to a well-known file instead of --output (`global`: git's per-user excludes file).
+    #[arg(long, value_enum, conflicts_with = "output", global = true)]
+    target: Option<Target>,
+
+    /// Resolve the output from the git repository root instead of the current directory.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "target", global = true)]
+    repo_root: bool,
+
+    /// Create missing parent directories of the output.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    parents: bool,
+
+    /// After writing, ask `git check-ignore` whether known build artifacts of each template
+    /// are really ignored.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    check_ignore: bool,
+
+    /// Drop patterns an earlier template already added, noting where each one lives.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    dedupe_lines: bool,
+
+    /// Sort patterns within each group so upstream reordering doesn't churn diffs.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    sort: bool,
+
+    /// Strip comments and blank lines from the templates.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    minify: bool,
+
+    /// Note each section's source URL, commit and fetch time in comments under its header.
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    provenance: bool,
+
+    /// Write a separate ignore file into each detected sub-project directory.
+    #[arg(
+        long,
+        visible_alias = "per-dir",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["types", "list"]
+    )]
+    monorepo: bool,
+
+    /// With --monorepo, look for sub-projects at any depth instead of only direct children.
+    #[arg(long, action = ArgAction::SetTrue, requires = "monorepo")]
+    recursive: bool,
+
+    /// Exit non-zero if template sections are missing or stale, without writing.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["list", "overwrite", "dry_run"])]
+    check: bool,
+
     /// Ignore cached type list and hit the API.
-    #[arg(long, action = ArgAction::SetTrue)]
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
     no_cache: bool,
 
-    /// Cache time-to-live for the type list, in minutes (default: 1 day).
-    #[arg(long, default_value_t = 60 * 24, value_name = "MINUTES")]
-    cache_ttl_minutes: u64,
+    /// Serve the template list and templates only from the cache, failing instead of
+    /// downloading anything.
+    #[arg(long, action = ArgAction::SetTrue, global = true, conflicts_with = "no_cache")]
+    locked: bool,
+
+    /// Don't look for new releases (checked at most once a day otherwise).
+    #[arg(long, action = ArgAction::SetTrue, global = true, env = "GI_NO_UPDATE_CHECK")]
+    no_update_check: bool,
+
+    /// Cache time-to-live for the type list, in minutes (default: 1 day).
+    #[arg(long, value_name = "MINUTES", global = true)]
+    cache_ttl_minutes: Option<u64>,
+
+    /// Largest the download cache may grow, e.g. 50M; least recently used files go first.
+    #[arg(long, value_name = "SIZE", value_parser = cache::parse_size, global = true)]
+    cache_max_size: Option<u64>,
+
+    /// Repository to fetch templates from, as OWNER/REPO[@REF] (default: github/gitignore).
+    #[arg(long, value_name = "SOURCE", global = true)]
+    source: Option<Source>,
+
+    /// GitHub Enterprise Server to fetch from (e.g. github.example.com), instead of github.com.
+    #[arg(long, value_name = "HOST", global = true, conflicts_with_all = ["api_url", "raw_url"])]
+    github_host: Option<String>,
+
+    /// Base URL of the GitHub API (default: https://api.github.com).
+    #[arg(long, value_name = "URL", global = true)]
+    api_url: Option<String>,
+
+    /// Base URL for raw file downloads (default: https://raw.githubusercontent.com).
+    #[arg(long, value_name = "URL", global = true)]
+    raw_url: Option<String>,
+
+    /// Config file to use instead of the default one.
+    #[arg(long, value_name = "PATH", global = true, env = "GI_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Verify downloaded templates against the checksums in MANIFEST (`sha256sum` output
+    /// you made from a checkout you trust; none ships with gi) before writing.
+    #[arg(long, value_name = "MANIFEST", global = true)]
+    verify: Option<PathBuf>,
+
+    /// Where to keep the undo journal (defaults to the platform data directory).
+    #[arg(long, value_name = "DIR", global = true, env = "GI_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Serve API and raw responses from a directory of canned files (unstable, for tests).
+    #[arg(long, value_name = "DIR", hide = true, global = true, env = "GI_FIXTURE_DIR")]
+    fixture_dir: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Target {
+    /// `core.excludesFile`, or `~/.config/git/ignore`; suits Global/macOS, Global/JetBrains, ...
+    Global,
+    /// `.dockerignore`, with patterns translated to Docker's matching rules.
+    Dockerignore,
+    /// `.npmignore`: what `npm publish` leaves out (gitignore syntax).
+    Npmignore,
+    /// `.prettierignore` (gitignore syntax).
+    Prettierignore,
+    /// `.eslintignore` (gitignore syntax).
+    Eslintignore,
+    /// `.ignore`, read by ripgrep, fd and ag on top of .gitignore.
+    Ignore,
+}
+
+impl Target {
+    /// The file this target writes in the project; `None` for git's global excludes.
+    fn file_name(self) -> Option<&'static str> {
+        match self {
+            Target::Global => None,
+            Target::Dockerignore => Some(".dockerignore"),
+            Target::Npmignore => Some(".npmignore"),
+            Target::Prettierignore => Some(".prettierignore"),
+            Target::Eslintignore => Some(".eslintignore"),
+            Target::Ignore => Some(".ignore"),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Print the cache directory.
+    Path,
+    /// List cached files with their size and age.
+    Info,
+    /// Delete the cache.
+    Clear {
+        /// Only forget the template list, keeping downloaded templates.
+        #[arg(long, action = ArgAction::SetTrue)]
+        types: bool,
+    },
+    /// Delete cached files older than an age such as `30d`, `12h` or `2w`.
+    Purge {
+        #[arg(long, value_name = "AGE", value_parser = cache::parse_age)]
+        older_than: Duration,
+    },
+    /// Pack the whole cache into a bundle (`.tar.zst`) for machines without network access.
+    Export { bundle: PathBuf },
+    /// Unpack a bundle made by `cache export` into the cache.
+    Import { bundle: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+enum HookAction {
+    /// Install a hook that runs `--check` on the output, blocking commits (or pushes)
+    /// while sections are missing or stale.
+    Install {
+        #[arg(long, value_enum, default_value = "pre-commit")]
+        kind: HookKind,
+        /// Replace an existing hook that wasn't installed by this tool.
+        #[arg(long, action = ArgAction::SetTrue)]
+        force: bool,
+    },
+}
+
+/// Spreadsheet-friendly catalog formats for `--list --format`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ListFormat {
+    Tsv,
+    Csv,
+}
+
+impl ListFormat {
+    fn separator(self) -> &'static str {
+        match self {
+            ListFormat::Tsv => "\t",
+            ListFormat::Csv => ",",
+        }
+    }
+
+    /// Escapes one cell: TSV has no quoting, so separators become spaces; CSV quotes per RFC 4180.
+    fn field(self, value: &str) -> String {
+        match self {
+            ListFormat::Tsv => value.replace(['\t', '\n', '\r'], " "),
+            ListFormat::Csv if value.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            }
+            ListFormat::Csv => value.to_string(),
+        }
+    }
+
+    fn row(self, cells: &[&str]) -> String {
+        let cells: Vec<String> = cells.iter().map(|cell| self.field(cell)).collect();
+        cells.join(self.separator())
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Restore the file changed by the most recent write.
+    Undo {
+        /// Restore even if the file was edited after it was written.
+        #[arg(long, action = ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Fetch template(s) into the output (same as passing types directly).
+    Get {
+        /// Template type(s) or `@profile`s to fetch (e.g. rust, @web). If omitted, a picker opens.
+        #[arg(value_name = "TYPE", num_args = 0..)]
+        types: Vec<String>,
+    },
+    /// Delete template sections from the output, leaving hand-written lines alone.
+    Remove {
+        /// Sections to remove. If omitted, a picker lists the sections in the file.
+        #[arg(value_name = "TYPE")]
+        types: Vec<String>,
+    },
+    /// Tidy the output file: trailing whitespace, repeated blank lines and patterns, and
+    /// old-style section headers.
+    Normalize,
+    /// Rearrange the output's template sections by `--order` (or the order of the templates
+    /// named), leaving hand-written lines where they are.
+    Reorder {
+        /// Sections to put first, in this order; the rest follow as they are.
+        types: Vec<String>,
+    },
+    /// Convert a Mercurial .hgignore (glob and regexp rules) and add it to the output as
+    /// an "Imported" section, listing rules with no gitignore equivalent.
+    ImportHg {
+        /// The .hgignore to read.
+        #[arg(default_value = ".hgignore")]
+        file: PathBuf,
+    },
+    /// Convert the output's patterns into `svn:global-ignores` and per-directory
+    /// `svn:ignore` property values, listing patterns Subversion can't express.
+    ExportSvn {
+        /// File to convert (defaults to the output).
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Set the properties with `svn propset` in the working copy holding the file,
+        /// instead of printing them.
+        #[arg(long, action = ArgAction::SetTrue)]
+        apply: bool,
+    },
+    /// Print a file with the template or snippet each line came from ("unmanaged" for
+    /// hand-written lines).
+    Explain {
+        /// File to explain (defaults to the output).
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Set up the first .gitignore of a project: detected templates and configured
+    /// defaults, adjusted in the picker (accepted as-is with --yes or without a terminal).
+    Init,
+    /// Put the templates into git's `init.templateDir` as `info/exclude`, so every
+    /// `git init` starts with them (setting `init.templateDir` if unset).
+    InstallGitTemplate {
+        /// Template type(s) or `@profile`s (defaults to the configured templates).
+        #[arg(value_name = "TYPE")]
+        types: Vec<String>,
+    },
+    /// Inspect or clean up the download cache.
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Manage git hooks that enforce up-to-date ignore sections.
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Rank templates by how many of the repository's untracked files they would ignore.
+    Suggest,
+    /// Replace this binary with the latest release, after verifying its SHA-256 checksum.
+    SelfUpdate {
+        /// Only report whether a newer release exists.
+        #[arg(long, action = ArgAction::SetTrue)]
+        check: bool,
+    },
+    /// Check connectivity, proxy and TLS settings, the cache and the output path, with hints
+    /// for anything that would make runs fail.
+    Doctor,
+    /// Make the ignore file match the repository's manifest: its templates (pinned ones at
+    /// their refs) in its order, dropping undeclared sections and keeping hand-written lines.
+    Sync {
+        /// Manifest to read.
+        #[arg(long, value_name = "PATH", default_value = sync::MANIFEST_FILE)]
+        manifest: PathBuf,
+    },
+    /// Report whether git would ignore each path under the output, and which rule decides.
+    Test {
+        /// Paths to check, relative to the current directory.
+        #[arg(value_name = "PATH", required = true, num_args = 1..)]
+        paths: Vec<PathBuf>,
+        /// Check against the output as it would be with these templates appended.
+        #[arg(long = "with", value_name = "TYPE", num_args = 1..)]
+        with: Vec<String>,
+    },
+    /// Report duplicate, shadowed, invalid and whitespace-trap patterns in the output.
+    Lint {
+        /// Apply the safe fixes (drop duplicate and shadowed lines, trim trailing spaces)
+        /// and show what changed.
+        #[arg(long, action = ArgAction::SetTrue)]
+        fix: bool,
+    },
+    /// Show how template sections in the output differ from upstream.
+    Diff {
+        /// Template type(s) to compare (e.g. rust, node).
+        #[arg(value_name = "TYPE", required = true, num_args = 1..)]
+        types: Vec<String>,
+    },
+    /// Search template names and print ranked matches.
+    Search {
+        /// Text to match against template names (fuzzy, case-insensitive).
+        query: String,
+    },
+    /// Find templates whose content contains a pattern.
+    Grep {
+        /// Text to look for (e.g. node_modules).
+        pattern: String,
+
+        /// Match case-insensitively.
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        ignore_case: bool,
+
+        /// Only search templates already in the local cache (no downloads).
+        #[arg(long, action = ArgAction::SetTrue)]
+        cached: bool,
+    },
+    /// Print one template with comments highlighted, without touching any files.
+    Show {
+        /// Template type to show (e.g. rust).
+        #[arg(value_name = "TYPE")]
+        name: String,
+
+        /// Pipe the output through $PAGER even when it fits on the screen.
+        #[arg(long, action = ArgAction::SetTrue)]
+        pager: bool,
+    },
+    /// Print template(s) to stdout (gibo-compatible `dump`).
+    Dump {
+        /// Template type(s) to print (e.g. Rust Node).
+        #[arg(value_name = "TYPE", required = true, num_args = 1..)]
+        types: Vec<String>,
+    },
+    /// Suggest templates based on the files in a project directory.
+    Detect {
+        /// Project directory to scan.
+        #[arg(value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+
+        /// Append the suggested templates to the output after confirming.
+        #[arg(long, action = ArgAction::SetTrue)]
+        apply: bool,
+
+        /// Walk nested directories (honoring ignore files) and report each sub-project.
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        recursive: bool,
+    },
+}
+
+impl Command {
+    /// Whether the command may reach GitHub, and so may as well look for a new release on
+    /// the way. Commands that only read or rewrite local files never wait on the network.
+    fn uses_network(&self) -> bool {
+        match self {
+            Command::Get { .. }
+            | Command::Init
+            | Command::InstallGitTemplate { .. }
+            | Command::Suggest
+            | Command::Sync { .. }
+            | Command::Diff { .. }
+            | Command::Search { .. }
+            | Command::Show { .. }
+            | Command::Dump { .. } => true,
+            Command::Test { with, .. } => !with.is_empty(),
+            Command::Grep { cached, .. } => !cached,
+            Command::Detect { apply, .. } => *apply,
+            Command::Undo { .. }
+            | Command::Remove { .. }
+            | Command::Normalize
+            | Command::Reorder { .. }
+            | Command::ImportHg { .. }
+            | Command::ExportSvn { .. }
+            | Command::Explain { .. }
+            | Command::Cache { .. }
+            | Command::Hook { .. }
+            | Command::SelfUpdate { .. }
+            | Command::Doctor
+            | Command::Lint { .. } => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedTypes {
+    fetched_at: u64,
+    /// Source the list came from; a different source means a cache miss.
+    #[serde(default)]
+    source: String,
+    types: Vec<String>,
+    #[serde(default)]
+    entries: Vec<TemplateInfo>,
+}
+
+impl CachedTypes {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        let fetched = UNIX_EPOCH + Duration::from_secs(self.fetched_at);
+        fetched.elapsed().map(|age| age <= ttl).unwrap_or(false)
+    }
+}
+
+fn main() {
+    if clipboard::is_holder() {
+        return clipboard::hold();
+    }
+    color::init();
+    if let Err(err) = run() {
+        eprintln!("{} {err}", style("Error:").red().bold().for_stderr());
+        // A distinct code lets wrappers offer the suggested templates instead.
+        let code = if err.is::<NothingDetected>() { 2 } else { 1 };
+        std::process::exit(code);
+    }
+}
+
+fn run() -> Result<(), DynError> {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    if args.first().is_some_and(gibo::invoked_as_gibo) {
+        args = gibo::translate_args(args);
+    }
+    let cli = Cli::parse_from(args);
+    verbosity::set_quiet(cli.quiet);
+    if cli.no_pager {
+        pager::disable();
+    }
+    let log_level = logging::level(cli.log_level, cli.verbose, cli.log_file.is_some());
+    logging::init(log_level, cli.log_file.as_deref())?;
+    let client = Client::builder().user_agent(USER_AGENT).build()?;
+    let config = Config::load(cli.config.as_deref())?;
+    let hosts = source_hosts(&cli, &config);
+    // `--source` alone, else `source` and then `sources` from the config, else github/gitignore.
+    // Each is on `hosts` unless its `sources` entry names its own.
+    let on_hosts = |source: &Source| Source { hosts: hosts.clone(), ..source.clone() };
+    let mut sources: Vec<Source> = match &cli.source {
+        Some(source) => vec![on_hosts(source)],
+        None => {
+            let first = config.source.as_ref().map(on_hosts);
+            let rest = config.sources.iter().filter_map(|entry| match entry {
+                SourceEntry::Plain(source) => Some(on_hosts(source)),
+                SourceEntry::Hosted(hosted) => Some(Source {
+                    hosts: hosts_for(&hosted.github_host, &hosted.api_url, &hosted.raw_url),
+                    ..hosted.source.clone()
+                }),
+                SourceEntry::Bundled(_) => None,
+            });
+            first.into_iter().chain(rest).collect()
+        }
+    };
+    sources.dedup();
+    if sources.is_empty() {
+        sources.push(on_hosts(&Source::default()));
+    }
+    let ttl_minutes = cli.cache_ttl_minutes.or(config.cache_ttl_minutes).unwrap_or(60 * 24);
+    let ttl = Duration::from_secs(ttl_minutes * 60);
+    let cache_max_size = match cli.cache_max_size {
+        Some(size) => Some(size),
+        None => config
+            .cache_max_size
+            .as_deref()
+            .map(|size| cache::parse_size(size).map_err(|err| format!("cache_max_size: {err}")))
+            .transpose()?,
+    };
+    let mut outputs = cli.output;
+    if let Some(target) = cli.target {
+        match target.file_name() {
+            Some(file_name) => outputs.push(PathBuf::from(file_name)),
+            None => outputs.push(git::global_excludes_file()?),
+        }
+    }
+    if outputs.is_empty() && !cli.clipboard {
+        outputs.push(config.output.unwrap_or_else(|| PathBuf::from(DEFAULT_OUTPUT)));
+    }
+    if cli.repo_root {
+        let root = git::repo_root(Path::new(".")).ok_or("Not inside a git repository")?;
+        for path in outputs.iter_mut().filter(|path| *path != Path::new(STDOUT)) {
+            *path = root.join(&path);
+        }
+    }
+    let outputs: Vec<PathBuf> = outputs.into_iter().map(output_file).collect();
+    if outputs.iter().any(|path| path == Path::new(STDOUT)) {
+        verbosity::status_to_stderr();
+    }
+    let detection_table = DetectionTable::with_config(&config.detect);
+
+    let manifest = cli.verify.as_deref().map(Manifest::load).transpose()?;
+    let snippets = Snippets::load(&config.snippets, &snippets_dir(cli.config.as_deref())?)?;
+    let aliases = config.aliases;
+    let profiles = config.profiles;
+    let lint_config = config.lint;
+    let resolve = |t: String| resolve_type(t, &aliases);
+    let fetcher = Fetcher {
+        client,
+        source: sources.remove(0),
+        fallbacks: sources,
+        bundle: match &cli.source {
+            Some(_) => None,
+            None => config.sources.iter().find_map(SourceEntry::bundle).map(Path::to_path_buf),
+        },
+        bundled: OnceCell::new(),
+        overrides: overrides_dir(cli.config.as_deref())?,
+        origins: RefCell::default(),
+        commits: RefCell::default(),
+        pins: BTreeMap::new(),
+        piped: None,
+        catalog_names: OnceCell::new(),
+        blob_shas: RefCell::default(),
+        manifest,
+        rules: config.rules,
+        snippets,
+        fixtures: cli.fixture_dir,
+        cache_max_size,
+        no_cache: cli.no_cache,
+        locked: cli.locked,
+    };
+
+    if cli.check_ignore
+        && let Some(file_name) = cli.target.and_then(Target::file_name)
+    {
+        return Err(format!("--check-ignore asks git, which doesn't read {file_name}").into());
+    }
+    let anchor = match (cli.before, cli.after) {
+        (Some(marker), _) => Anchor::Before(marker),
+        (_, Some(marker)) => Anchor::After(marker),
+        _ if cli.at_top => Anchor::Top,
+        _ => Anchor::End,
+    };
+    let options = WriteOptions {
+        overwrite: cli.overwrite,
+        anchor,
+        order: cli.order.or(config.order).unwrap_or_default(),
+        clipboard: cli.clipboard,
+        edit: cli.edit,
+        dockerignore: cli.target == Some(Target::Dockerignore),
+        line_ending: match (cli.crlf, cli.lf) {
+            (true, _) => Some(LineEnding::Crlf),
+            (_, true) => Some(LineEnding::Lf),
+            _ => None,
+        },
+        dry_run: cli.dry_run,
+        assume_yes: cli.yes,
+        diff: cli.diff,
+        // `~/.config/git/` often doesn't exist yet.
+        parents: cli.parents || cli.target == Some(Target::Global),
+        check_ignore: cli.check_ignore,
+        dedupe_lines: cli.dedupe_lines,
+        sort: cli.sort,
+        minify: cli.minify,
+        backup: if cli.no_backup {
+            None
+        } else {
+            cli.backup.or(config.backup.and_then(|setting| setting.suffix()))
+        },
+        journal: Some(journal::dir(cli.data_dir.as_deref())?),
+        post_write: config.post_write,
+        provenance: cli.provenance || config.provenance,
+    };
+
+    let online = !cli.locked && cli.command.as_ref().is_none_or(Command::uses_network);
+    if online && !cli.no_update_check && config.update_check != Some(false) {
+        notify_new_release(&fetcher);
+    }
+
+    let mut requested = cli.types;
+    match cli.command {
+        Some(Command::Get { types }) => requested = types,
+        Some(Command::Undo { force }) => {
+            let journal_dir = options.journal.as_deref().ok_or("No undo journal")?;
+            let entry = journal::undo(journal_dir, force)?;
+            status!(
+                "Restored {} (undid {})",
+                entry.path.display(),
+                entry.templates.join(", ")
+            );
+            return Ok(());
+        }
+        Some(Command::Remove { types }) => {
+            let types = expand_profiles(types, &profiles)?;
+            let types: Vec<String> = types.into_iter().map(resolve).collect();
+            let output = single_output(&outputs, "remove")?;
+            return remove_sections(&fetcher, output, types, &options);
+        }
+        Some(Command::Normalize) => {
+            return normalize_file(single_output(&outputs, "normalize")?, &options);
+        }
+        Some(Command::Reorder { types }) => {
+            let types = if types.is_empty() { config.templates } else { types };
+            let types = expand_profiles(types, &profiles)?;
+            let normalized: Vec<String> = types.into_iter().map(resolve).collect();
+            let output = single_output(&outputs, "reorder")?;
+            return reorder_file(&fetcher, output, &normalized, &options);
+        }
+        Some(Command::ImportHg { file }) => {
+            return import_hgignore(&fetcher, &file, &outputs, &options);
+        }
+        Some(Command::ExportSvn { file, apply }) => {
+            let file = match &file {
+                Some(file) => file.as_path(),
+                None => single_output(&outputs, "export-svn")?,
+            };
+            return export_svn(file, apply, &options);
+        }
+        Some(Command::Explain { file }) => {
+            let file = match &file {
+                Some(file) => file.as_path(),
+                None => single_output(&outputs, "explain")?,
+            };
+            return explain_file(&fetcher, file);
+        }
+        Some(Command::Init) => {
+            let output = single_output(&outputs, "init")?;
+            if !options.overwrite && fs::metadata(output).is_ok_and(|meta| meta.len() > 0) {
+                let hint = "add templates with `gi <type>`, or pass --overwrite";
+                return Err(format!("{} already exists ({hint})", output.display()).into());
+            }
+            let types = fetcher.load_types(cli.no_cache, ttl)?;
+            let table = &detection_table;
+            let configured = &config.templates;
+            let selected =
+                init_selection(&fetcher, table, configured, &types, &profiles, &options)?;
+            let selected = expand_profiles(selected, &profiles)?;
+            let mut normalized: Vec<String> = selected.into_iter().map(resolve).collect();
+            for always in config.always.into_iter().map(resolve) {
+                if !normalized.iter().any(|t| names::eq_folded(t, &always)) {
+                    normalized.push(always);
+                }
+            }
+            return apply_templates(&fetcher, &normalized, &outputs, &options);
+        }
+        Some(Command::InstallGitTemplate { types }) => {
+            let types = if types.is_empty() { config.templates } else { types };
+            if types.is_empty() {
+                return Err("No templates given or configured (`templates` in the config)".into());
+            }
+            let types = expand_profiles(types, &profiles)?;
+            let normalized: Vec<String> = types.into_iter().map(resolve).collect();
+            let dir = match git::template_dir() {
+                Some(dir) => dir,
+                None => {
+                    let dir = git::home_dir()?.join(DEFAULT_GIT_TEMPLATE_DIR);
+                    git::set_global_config("init.templateDir", &dir.to_string_lossy())?;
+                    status!("Set init.templateDir to {}", dir.display());
+                    dir
+                }
+            };
+            let options = WriteOptions {
+                overwrite: true,
+                parents: true,
+                ..options
+            };
+            let exclude = dir.join("info").join("exclude");
+            return apply_templates(&fetcher, &normalized, &[exclude], &options);
+        }
+        Some(Command::Cache { action }) => return manage_cache(action),
+        Some(Command::Hook {
+            action: HookAction::Install { kind, force },
+        }) => {
+            return install_hook(single_output(&outputs, "hook install")?, kind, force);
+        }
+        Some(Command::Doctor) => return run_doctor(&fetcher, &outputs),
+        Some(Command::SelfUpdate { check }) => {
+            return self_update(&fetcher, check || options.dry_run);
+        }
+        Some(Command::Suggest) => {
+            let types = fetcher.load_types(cli.no_cache, ttl)?;
+            let candidates = suggestion_candidates(&fetcher, &types, &detection_table);
+            return suggest_templates(&fetcher, &candidates, &outputs, &options);
+        }
+        Some(Command::Sync { manifest: path }) => {
+            let manifest = sync::Manifest::load(&path)?;
+            if manifest.templates.is_empty() {
+                return Err(format!("{} declares no templates", path.display()).into());
+            }
+            let output = manifest.output_path(&path);
+            let types = expand_profiles(manifest.templates, &profiles)?;
+            let normalized: Vec<String> = types.into_iter().map(resolve).collect();
+            let mut fetcher = fetcher;
+            fetcher.snippets.extend(&manifest.snippets);
+            fetcher.pins =
+                manifest.pins.into_iter().map(|(name, pin)| (resolve(name), pin)).collect();
+            return sync_file(&fetcher, &output, &normalized, &options);
+        }
+        Some(Command::Test { paths, with }) => {
+            let with = expand_profiles(with, &profiles)?;
+            let proposed: Vec<String> = with.into_iter().map(resolve).collect();
+            return test_paths(&fetcher, single_output(&outputs, "test")?, &proposed, &paths);
+        }
+        Some(Command::Lint { fix }) => {
+            let output = single_output(&outputs, "lint")?;
+            if fix {
+                fix_lint(output, &lint_config, &options)?;
+            }
+            return lint_file(output, &lint_config);
+        }
+        Some(Command::Diff { types }) => {
+            let types = expand_profiles(types, &profiles)?;
+            let normalized: Vec<String> = types.into_iter().map(resolve).collect();
+            return diff_sections(&fetcher, single_output(&outputs, "diff")?, &normalized);
+        }
+        Some(Command::Search { query }) => {
+            let catalog = fetcher.load_catalog(cli.no_cache, ttl)?;
+            let types: Vec<String> = catalog.iter().map(|info| info.name.clone()).collect();
+            let matches = names::fuzzy_rank(&types, &query);
+            if matches.is_empty() {
+                return Err(format!("No templates match '{query}'").into());
+            }
+            if cli.json {
+                let hits: Vec<TemplateInfo> =
+                    matches.iter().map(|&idx| catalog[idx].clone()).collect();
+                return print_catalog_json(&fetcher, &hits, &[]);
+            }
+            matches.iter().for_each(|&idx| println!("{}", types[idx]));
+            return Ok(());
+        }
+        Some(Command::Grep {
+            pattern,
+            ignore_case,
+            cached,
+        }) => {
+            let types = fetcher.load_types(cli.no_cache, ttl)?;
+            return grep_templates(&fetcher, &types, &pattern, ignore_case, cached);
+        }
+        Some(Command::Show { name, pager }) => {
+            let template = fetcher.fetch(&resolve(name))?;
+            let text = highlight_template(&template.content);
+            if pager {
+                return pager::page(&text);
+            }
+            return pager::print(&text);
+        }
+        Some(Command::Dump { types }) => {
+            let types = expand_profiles(types, &profiles)?;
+            let normalized: Vec<String> = types.into_iter().map(resolve).collect();
+            if cli.json {
+                return print_templates_json(&fetcher, &normalized);
+            }
+            let options = WriteOptions {
+                dry_run: true,
+                ..WriteOptions::default()
+            };
+            return apply_templates(&fetcher, &normalized, &outputs, &options);
+        }
+        Some(Command::Detect {
+            dir,
+            apply,
+            recursive: true,
+        }) => {
+            let output = single_output(&outputs, "detect --recursive")?;
+            return detect_recursive(&fetcher, &dir, &detection_table, apply, output, &options);
+        }
+        Some(Command::Detect { dir, apply, .. }) => {
+            return detect(&fetcher, &dir, &detection_table, apply, &outputs, &options);
+        }
+        None => {}
+    }
+
+    let filter = ListFilter { name: cli.filter, category: cli.category };
+    if let Some(format) = cli.format {
+        let mut catalog = fetcher.load_catalog(cli.no_cache, ttl)?;
+        catalog.retain(|info| filter.matches(&info.name));
+        let mut failures = Failures::default();
+        if cli.dates {
+            fetcher.fill_modified(&mut catalog, &mut failures);
+        }
+        println!("{}", format.row(&["name", "category", "size", "last_updated"]));
+        for info in &catalog {
+            let size = info.size.to_string();
+            let modified = info.modified.as_deref().unwrap_or("");
+            let category = Category::of(&info.name).as_str();
+            println!("{}", format.row(&[&info.name, category, &size, modified]));
+        }
+        return failures.into_result();
+    }
+
+    let snippet_names: Vec<String> =
+        fetcher.snippets.names().into_iter().filter(|name| filter.matches(name)).collect();
+    if cli.list && cli.long {
+        let mut catalog = fetcher.load_catalog(cli.no_cache, ttl)?;
+        catalog.retain(|info| filter.matches(&info.name));
+        let mut failures = Failures::default();
+        if cli.dates {
+            fetcher.fill_modified(&mut catalog, &mut failures);
+        }
+        if cli.json {
+            print_catalog_json(&fetcher, &catalog, &snippet_names)?;
+            return failures.into_result();
+        }
+        let width = catalog.iter().map(|info| info.name.chars().count()).max().unwrap_or(0);
+        for info in &catalog {
+            let modified = info.modified.as_deref().unwrap_or("-");
+            let sha: String = info.sha.chars().take(7).collect();
+            let description = fetcher.describe(info).unwrap_or_default();
+            let (size, name) = (info.size, &info.name);
+            let line =
+                format!("{size:>7}  {modified:<10}  {sha:<7}  {name:<width$}  {description}");
+            println!("{}", line.trim_end());
+        }
+        return failures.into_result();
+    }
+
+    if cli.list && cli.json {
+        let mut catalog = fetcher.load_catalog(cli.no_cache, ttl)?;
+        catalog.retain(|info| filter.matches(&info.name));
+        return print_catalog_json(&fetcher, &catalog, &snippet_names);
+    }
+
+    if cli.list {
+        let mut types = fetcher.load_types(cli.no_cache, ttl)?;
+        types.retain(|t| filter.matches(t));
+        types.extend(snippet_names);
+        if cli.flat || !io::stdout().is_terminal() {
+            types.iter().for_each(|t| println!("{t}"));
+            return Ok(());
+        }
+        let mut overrides = listing::override_names(&fetcher.overrides)?;
+        overrides.retain(|name| filter.matches(name));
+        let width = usize::from(console::Term::stdout().size().1);
+        return pager::print(&listing::render_groups(&types, &overrides, width));
+    }
+
+    for path in &cli.from_file {
+        requested.extend(config::read_template_list(path)?);
+    }
+    let mut fetcher = fetcher;
+    if cli.stdin.is_some() || requested.iter().any(|t| t == STDIN) {
+        let name = cli.stdin.clone().unwrap_or_else(|| STDIN_TEMPLATE.to_string());
+        match requested.iter_mut().find(|t| *t == STDIN) {
+            Some(slot) => *slot = name.clone(),
+            None => requested.push(name.clone()),
+        }
+        requested.retain(|t| t != STDIN);
+        fetcher.piped = Some((name, read_piped_template()?));
+    }
+
+    if cli.check {
+        let requested = expand_profiles(requested, &profiles)?;
+        let normalized: Vec<String> = requested.into_iter().map(resolve).collect();
+        return check_sections(&fetcher, single_output(&outputs, "--check")?, &normalized);
+    }
+
+    if cli.monorepo {
+        let subprojects = detect_subprojects(Path::new("."), &detection_table, cli.recursive);
+        let output = single_output(&outputs, "--monorepo")?;
+        return apply_per_directory(&fetcher, subprojects, output, &options);
+    }
+
+    let mut selected = requested;
+    if selected.is_empty() {
+        selected = config.templates;
+    }
+    if selected.is_empty() && (cli.yes || !is_interactive()) {
+        let detections = detect_templates(Path::new("."), &detection_table);
+        if detections.is_empty() {
+            return Err(NothingDetected::new(Path::new(".")).into());
+        }
+        // Scripts must opt in before guesses are written on their behalf.
+        if !cli.yes {
+            return Err(NothingDetected::unconfirmed(Path::new("."), detections).into());
+        }
+        selected.extend(detections.into_iter().map(|d| d.template));
+    }
+    if selected.is_empty() {
+        let available = fetcher.load_types(cli.no_cache, ttl)?;
+        selected = prompt_for_types(&fetcher, &available, &[], &filter)?;
+    }
+
+    let selected = expand_profiles(selected, &profiles)?;
+    let mut normalized: Vec<String> = selected.into_iter().map(resolve).collect();
+    for always in config.always.into_iter().map(resolve) {
+        if !normalized.iter().any(|t| names::eq_folded(t, &always)) {
+            normalized.push(always);
+        }
+    }
+
+    if cli.dry_run && cli.json {
+        return print_templates_json(&fetcher, &normalized);
+    }
+    apply_templates(&fetcher, &normalized, &outputs, &options)
+}
+
+/// How fetched templates reach the output file.
+#[derive(Debug, Clone, Default)]
+struct WriteOptions {
+    /// Replace the file instead of appending missing sections.
+    overwrite: bool,
+    /// Where missing sections go (`--before`, `--after`, `--at-top`).
+    anchor: Anchor,
+    /// Order the templates are written in (`--order`).
+    order: SectionOrder,
+    /// Also put the merged templates on the clipboard (`--clipboard`).
+    clipboard: bool,
+    /// Let the user edit the result before it is written (`--edit`).
+    edit: bool,
+    /// Rewrite patterns for Docker (`--target dockerignore`).
+    dockerignore: bool,
+    /// Line endings to write (`--crlf`, `--lf`); `None` keeps the existing file's.
+    line_ending: Option<LineEnding>,
+    /// Print instead of writing.
+    dry_run: bool,
+    /// Don't ask before destructive changes (`--yes`).
+    assume_yes: bool,
+    /// Show a diff of the pending change first (`--diff`).
+    diff: bool,
+    /// Create missing parent directories (`--parents`).
+    parents: bool,
+    /// Verify the result with `git check-ignore` (`--check-ignore`).
+    check_ignore: bool,
+    /// Remove patterns repeated across templates (`--dedupe-lines`).
+    dedupe_lines: bool,
+    /// Sort patterns within each group (`--sort`).
+    sort: bool,
+    /// Keep only pattern lines (`--minify`).
+    minify: bool,
+    /// Suffix for a copy of the existing file made before it changes.
+    backup: Option<String>,
+    /// Undo journal directory; `None` skips journaling.
+    journal: Option<PathBuf>,
+    /// Shell commands run after each write (`post_write` in the config).
+    post_write: Vec<String>,
+    /// Note each section's source, commit and fetch time under its header (`--provenance`).
+    provenance: bool,
+}
+
+/// A directory given as an output stands for the `.gitignore` inside it.
+fn output_file(path: PathBuf) -> PathBuf {
+    if path.is_dir() {
+        path.join(DEFAULT_OUTPUT)
+    } else {
+        path
+    }
+}
+
+/// The output for commands that work on one file, rejecting repeated `--output`.
+fn single_output<'a>(outputs: &'a [PathBuf], command: &str) -> Result<&'a Path, DynError> {
+    match outputs {
+        [output] => Ok(output),
+        _ => Err(format!("{command} takes a single --output").into()),
+    }
+}
+
+/// Fetches `types` once and writes them to each of `outputs`.
+fn apply_templates(
+    fetcher: &Fetcher,
+    types: &[String],
+    outputs: &[PathBuf],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let mut failures = Failures::default();
+    let templates = prepare_templates(fetcher, types, options, &mut failures)?;
+    if options.clipboard && !options.dry_run && !templates.is_empty() {
+        failures.capture("clipboard", || {
+            clipboard::copy(&render_output("", true, &Anchor::End, &templates)?.0)?;
+            let names: Vec<&str> = templates.iter().map(|tpl| tpl.name.as_str()).collect();
+            status!("Copied {} to the clipboard", names.join(", "));
+            Ok(())
+        });
+    }
+    // A plain dry run prints the same sections for every output; once is enough.
+    let outputs = if options.dry_run && !options.diff {
+        &outputs[..outputs.len().min(1)]
+    } else {
+        outputs
+    };
+    for output in outputs {
+        info!(
+            output = %output.display(),
+            fetched = templates.len(),
+            failed = types.len() - templates.len(),
+            "applying templates"
+        );
+        if !templates.is_empty() {
+            failures.capture(output.display().to_string(), || {
+                write_templates(output, options, &templates)?;
+                if options.check_ignore && !options.dry_run && output != Path::new(STDOUT) {
+                    verify_ignored(output, &templates)?;
+                }
+                Ok(())
+            });
+        }
+    }
+    failures.into_result()
+}
+
+/// Fetches `types` and applies the `--order`, `--dedupe-lines` (which merges them into one
+/// section), `--sort`, `--minify` and `--provenance` processing, recording fetch failures
+/// in `failures`.
+fn prepare_templates(
+    fetcher: &Fetcher,
+    types: &[String],
+    options: &WriteOptions,
+    failures: &mut Failures,
+) -> Result<Vec<Template>, DynError> {
+    let mut templates = fetcher.fetch_all(types, failures);
+    let mut transforms = Transforms {
+        dedupe_lines: false,
+        dockerignore: options.dockerignore,
+        sort: options.sort,
+        minify: options.minify,
+    };
+    if options.order == SectionOrder::Alphabetical {
+        templates.sort_by_cached_key(|tpl| names::fold(&tpl.name));
+    }
+    warn_negation_conflicts(&templates);
+    if options.dedupe_lines && templates.len() > 1 {
+        let names: Vec<&str> = templates.iter().map(|tpl| tpl.name.as_str()).collect();
+        let parts = templates.iter().map(|tpl| (tpl.name.as_str(), tpl.content.as_str()));
+        let merged = Template {
+            name: names.join(tidy::MERGED_SEPARATOR),
+            content: tidy::merge(parts),
+            provenance: None,
+        };
+        templates = vec![merged];
+        transforms.dedupe_lines = true;
+    }
+    for tpl in &mut templates {
+        tpl.content = transforms.apply(&tpl.content);
+        tpl.provenance = transforms.comment();
+    }
+    if options.provenance {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        for tpl in &mut templates {
+            if let Some(lines) = fetcher.provenance(&tpl.name, now) {
+                tpl.provenance.get_or_insert_default().push_str(&lines);
+            }
+        }
+    }
+    Ok(templates)
+}
+
+/// Warns when one template's `!pattern` fights another's pattern, since only the write
+/// order decides which wins, and suggests an order that keeps the negations.
+fn warn_negation_conflicts(templates: &[Template]) {
+    let sections: Vec<(&str, &str)> =
+        templates.iter().map(|tpl| (tpl.name.as_str(), tpl.content.as_str())).collect();
+    let conflicts = lint::negation_conflicts(&sections);
+    for conflict in &conflicts {
+        let outcome = if conflict.negation_loses {
+            format!("{} comes later, so the path stays ignored", conflict.ignoring)
+        } else {
+            "swapping them would ignore it again".to_string()
+        };
+        note!(
+            "Warning: `{}` in {} re-includes what `{}` in {} ignores; {outcome}",
+            conflict.negation,
+            conflict.negating,
+            conflict.pattern,
+            conflict.ignoring
+        );
+    }
+    let names: Vec<&str> = sections.iter().map(|(name, _)| *name).collect();
+    let suggested = lint::resolution_order(&names, &conflicts);
+    if suggested != names {
+        note!("To keep the negations, apply in this order: {}", suggested.join(", "));
+    }
+}
+
+/// `--check-ignore`: confirms with git that the patterns written to `output` ignore each
+/// template's sample artifacts, catching merge and ordering mistakes.
+fn verify_ignored(output: &Path, templates: &[Template]) -> Result<(), DynError> {
+    let expected: Vec<(&str, &str)> = templates
+        .iter()
+        .flat_map(|tpl| artifacts::samples(&tpl.name).iter().map(|path| (tpl.name.as_str(), *path)))
+        .collect();
+    if expected.is_empty() {
+        note!("No sample artifacts on record to check {}", output.display());
+        return Ok(());
+    }
+    let paths: Vec<&str> = expected.iter().map(|(_, path)| *path).collect();
+    let ignored = git::check_ignore(output, &paths)?;
+    let missed: Vec<String> = expected
+        .iter()
+        .filter(|(_, path)| !ignored.iter().any(|hit| hit == path))
+        .map(|(name, path)| format!("{path} ({name})"))
+        .collect();
+    if !missed.is_empty() {
+        return Err(format!("git does not ignore {}", missed.join(", ")).into());
+    }
+    status!("git ignores all {} sample artifact(s)", expected.len());
+    Ok(())
+}
+
+/// Writes detected templates into each sub-project, reusing the output's file name.
+fn apply_per_directory(
+    fetcher: &Fetcher,
+    subprojects: Vec<(PathBuf, Vec<Detection>)>,
+    output: &Path,
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    if subprojects.is_empty() {
+        return Err("No sub-projects detected".into());
+    }
+    let file_name = output.file_name().ok_or("Output path has no file name")?;
+
+    let mut failures = Failures::default();
+    for (dir, detections) in subprojects {
+        let target = dir.join(file_name);
+        let types: Vec<String> = detections.into_iter().map(|d| d.template).collect();
+        status!("{}: {}", target.display(), types.join(", "));
+        failures.capture(target.display().to_string(), || {
+            apply_templates(fetcher, &types, slice::from_ref(&target), options)
+        });
+    }
+    failures.into_result()
+}
+
+fn detect_recursive(
+    fetcher: &Fetcher,
+    root: &Path,
+    table: &DetectionTable,
+    apply: bool,
+    output: &Path,
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let subprojects = detect_subprojects(root, table, true);
+    if subprojects.is_empty() {
+        return Err(NothingDetected::new(root).into());
+    }
+    for (dir, detections) in &subprojects {
+        let dir = dir.strip_prefix(root).unwrap_or(dir);
+        let summary: Vec<String> = detections
+            .iter()
+            .map(|d| format!("{} (found {})", d.template, d.evidence))
+            .collect();
+        println!("{}: {}", dir.display(), summary.join(", "));
+    }
+    if !apply {
+        return Ok(());
+    }
+
+    let prompt = format!("Write ignore files into {} directories?", subprojects.len());
+    if !confirm(&prompt, options.assume_yes)? {
+        return Ok(());
+    }
+    apply_per_directory(fetcher, subprojects, output, options)
+}
+
+fn detect(
+    fetcher: &Fetcher,
+    dir: &Path,
+    table: &DetectionTable,
+    apply: bool,
+    outputs: &[PathBuf],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let detections = detect_templates(dir, table);
+    if detections.is_empty() {
+        return Err(NothingDetected::new(dir).into());
+    }
+    for detection in &detections {
+        println!("{} (found {})", detection.template, detection.evidence);
+    }
+    if !apply {
+        return Ok(());
+    }
+
+    let types: Vec<String> = detections.into_iter().map(|d| d.template).collect();
+    let targets: Vec<String> = outputs.iter().map(|path| path.display().to_string()).collect();
+    let prompt = format!("Append {} to {}?", types.join(", "), targets.join(", "));
+    if !confirm(&prompt, options.assume_yes)? {
+        return Ok(());
+    }
+    apply_templates(fetcher, &types, outputs, options)
+}
+
+#[derive(Deserialize)]
+struct RepoTree {
+    tree: Vec<RepoEntry>,
+    /// Set when the repository is too large to list in one response.
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Deserialize)]
+struct RepoEntry {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct CommitEntry {
+    commit: CommitDetail,
+}
+
+#[derive(Deserialize)]
+struct CommitDetail {
+    committer: CommitSignature,
+}
+
+#[derive(Deserialize)]
+struct CommitSignature {
+    date: String,
+}
+
+/// A catalog entry: template name plus metadata from the GitHub API.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct TemplateInfo {
+    name: String,
+    size: u64,
+    /// Git blob SHA of the template file.
+    sha: String,
+    /// Date (YYYY-MM-DD) of the last commit touching the template, fetched on demand.
+    #[serde(default)]
+    modified: Option<String>,
+}
+
+/// One `--json` listing entry.
+#[derive(Serialize, Debug)]
+struct ListEntry {
+    name: String,
+    /// `root`, `global`, or `community` for upstream templates; `snippet` for local ones.
+    category: &'static str,
+    source: String,
+    size: Option<u64>,
+    sha: Option<String>,
+    modified: Option<String>,
+    /// The template's first comment line, when known without downloading it.
+    description: Option<String>,
+    /// Whether the template content is in the local cache.
+    cached: bool,
+    /// When the cached catalog was fetched (Unix seconds); `None` if it isn't cached.
+    fetched_at: Option<u64>,
+}
+
+fn print_catalog_json(
+    fetcher: &Fetcher,
+    catalog: &[TemplateInfo],
+    snippet_names: &[String],
+) -> Result<(), DynError> {
+    let fetched_at = fetcher.catalog_fetched_at();
+    let mut entries: Vec<ListEntry> = catalog
+        .iter()
+        .map(|info| ListEntry {
+            name: info.name.clone(),
+            category: Category::of(&info.name).as_str(),
+            source: fetcher.source.to_string(),
+            size: Some(info.size),
+            sha: Some(info.sha.clone()),
+            modified: info.modified.clone(),
+            description: fetcher.describe(info),
+            cached: fetcher.is_cached(&info.name),
+            fetched_at,
+        })
+        .collect();
+    entries.extend(snippet_names.iter().map(|name| ListEntry {
+        name: name.clone(),
+        category: Category::Snippet.as_str(),
+        source: "local".to_string(),
+        size: None,
+        sha: None,
+        modified: None,
+        description: fetcher.local_description(name),
+        cached: true,
+        fetched_at: None,
+    }));
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+/// One `--dry-run --json` entry: a fetched template as it would be written.
+#[derive(Serialize, Debug)]
+struct DryRunEntry<'a> {
+    name: &'a str,
+    /// Raw URL the template is fetched from; `None` for snippets.
+    url: Option<String>,
+    reference: Option<&'a str>,
+    bytes: usize,
+    content: &'a str,
+}
+
+fn print_templates_json(fetcher: &Fetcher, types: &[String]) -> Result<(), DynError> {
+    let mut failures = Failures::default();
+    let templates = fetcher.fetch_all(types, &mut failures);
+    let entries: Vec<DryRunEntry> = templates
+        .iter()
+        .map(|tpl| {
+            let upstream = fetcher.snippets.get(&tpl.name).is_none();
+            DryRunEntry {
+                name: &tpl.name,
+                url: upstream.then(|| fetcher.source.raw_url(&tpl.name)),
+                reference: upstream.then_some(fetcher.source.reference.as_str()),
+                bytes: tpl.content.len(),
+                content: &tpl.content,
+            }
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    failures.into_result()
+}
+
+/// Prompts need a terminal on both ends; dialoguer draws on stderr.
+fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Asks a yes/no question, or assumes yes under `--yes`. Without a terminal there is
+/// nobody to ask, so that's an error rather than a silent default.
+fn confirm(prompt: &str, assume_yes: bool) -> Result<bool, DynError> {
+    if assume_yes {
+        return Ok(true);
+    }
+    if !is_interactive() {
+        return Err(format!("{prompt} (no terminal to confirm; pass --yes to proceed)").into());
+    }
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(true)
+        .interact()?)
+}
+
+/// Asks which of `candidates` a partial template name meant; without a terminal the
+/// name has to be spelled out.
+fn choose_template<'a>(name: &str, candidates: &[&'a str]) -> Result<&'a str, DynError> {
+    const SHOWN: usize = 10;
+    if !is_interactive() {
+        let listed = candidates.iter().take(SHOWN).copied().collect::<Vec<_>>().join(", ");
+        return Err(format!("`{name}` could be any of {listed}; give the full name").into());
+    }
+    let shown = &candidates[..candidates.len().min(SHOWN)];
+    let index = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Which template did `{name}` mean?"))
+        .items(shown)
+        .default(0)
+        .interact()?;
+    Ok(shown[index])
+}
+
+/// Picker over upstream templates plus snippets, previewing whatever is available locally.
+/// `filter` (`--filter`, `--category`) narrows what is offered.
+fn prompt_for_types(
+    fetcher: &Fetcher,
+    types: &[String],
+    preselected: &[String],
+    filter: &ListFilter,
+) -> Result<Vec<String>, DynError> {
+    let mut items = types.to_vec();
+    items.extend(fetcher.snippets.names());
+    items.retain(|item| filter.matches(item) || preselected.contains(item));
+    let describe = |name: &str| fetcher.local_description(name);
+    picker::pick_many("Select gitignore templates", &items, preselected, describe, |name| {
+        match fetcher.snippets.get(name) {
+            Some(snippet) => snippet.ok().map(|snippet| snippet.body.clone()),
+            None => read_cached_template(&fetcher.source, name),
+        }
+    })
+}
+
+#[derive(Debug)]
+struct Template {
+    name: String,
+    content: String,
+    /// Comment lines written under the header: the options that rewrote the content
+    /// (see `tidy::Transforms`) and `--provenance` notes.
+    provenance: Option<String>,
+}
+
+/// Where a template's content came from, for `--provenance`.
+#[derive(Debug, Clone)]
+struct Origin {
+    url: String,
+    /// The source it was downloaded from; `None` for local overrides.
+    source: Option<Source>,
+}
+
+/// Downloads templates, verifying checksums and applying configured rules.
+struct Fetcher {
+    client: Client,
+    source: Source,
+    manifest: Option<Manifest>,
+    rules: BTreeMap<String, TemplateRules>,
+    snippets: Snippets,
+    /// Canned responses served instead of the network (see `--fixture-dir`).
+    fixtures: Option<PathBuf>,
+    /// Bytes the cache may hold before least recently used files are evicted.
+    cache_max_size: Option<u64>,
+    /// `--no-cache`: also retry templates recently found missing.
+    no_cache: bool,
+    /// `--locked`: never touch the network, only the cache.
+    locked: bool,
+    /// Sources tried in order for templates `source` doesn't have (`sources` in the config).
+    fallbacks: Vec<Source>,
+    /// Cache bundle tried after every source (a `bundle` entry in `sources`).
+    bundle: Option<PathBuf>,
+    /// The templates in `bundle`, read on first use.
+    bundled: OnceCell<HashMap<PathBuf, String>>,
+    /// Local `<Name>.gitignore` files used instead of the upstream templates.
+    overrides: PathBuf,
+    /// Where each downloaded template came from.
+    origins: RefCell<HashMap<String, Origin>>,
+    /// Commit each source's ref resolved to, looked up once per run.
+    commits: RefCell<HashMap<String, Option<String>>>,
+    /// Templates fetched at another ref of `source` (`pins` in `.gitignore.toml`).
+    pins: BTreeMap<String, String>,
+    /// A template read from stdin (`-` or `--stdin NAME`): its name and content.
+    piped: Option<(String, String)>,
+    /// Upstream template names, looked up once per run to fix the casing of typed ones.
+    catalog_names: OnceCell<Vec<String>>,
+    /// Blob SHA of each template in a source's cached catalog, read once per run.
+    blob_shas: RefCell<HashMap<String, HashMap<String, String>>>,
+}
+
+impl Fetcher {
+    fn load_types(&self, no_cache: bool, ttl: Duration) -> Result<Vec<String>, DynError> {
+        let catalog = self.load_catalog(no_cache, ttl)?;
+        Ok(catalog.into_iter().map(|info| info.name).collect())
+    }
+
+    fn load_catalog(&self, no_cache: bool, ttl: Duration) -> Result<Vec<TemplateInfo>, DynError> {
+        if self.fixtures.is_some() {
+            // Fixture runs stay hermetic: the user's cache is neither read nor written.
+            return self.fetch_catalog();
+        }
+        if self.locked {
+            let cached = read_cached_types(&self.source, Duration::MAX)?;
+            let missing = || format!("No cached template list for {} (--locked)", self.source);
+            return Ok(cached.ok_or_else(missing)?);
+        }
+        if no_cache {
+            info!(source = %self.source, "ignoring cached template list (--no-cache)");
+        } else if let Some(cached) = read_cached_types(&self.source, ttl)? {
+            info!(source = %self.source, "using cached template list");
+            return Ok(cached);
+        } else {
+            info!(source = %self.source, "template list not cached or stale");
+        }
+        let fresh = match self.fetch_catalog() {
+            Ok(fresh) => fresh,
+            // Offline (say, with an imported cache bundle): an old list beats none.
+            Err(err) => match read_cached_types(&self.source, Duration::MAX) {
+                Ok(Some(stale)) if !no_cache => {
+                    note!("Warning: {err}; using the cached template list");
+                    return Ok(stale);
+                }
+                _ => return Err(err),
+            },
+        };
+        write_cached_types(&self.source, &fresh)?;
+        Ok(fresh)
+    }
+
+    fn fetch_catalog(&self) -> Result<Vec<TemplateInfo>, DynError> {
+        let listing: RepoTree = match &self.fixtures {
+            Some(dir) => serde_json::from_str(&fs::read_to_string(dir.join(FIXTURE_TYPES_FILE))?)?,
+            None => {
+                let message = format!("Fetching template list from {}", self.source);
+                let _spinner = progress::spinner(&message);
+                let res = self.send(self.api_get(&self.source.tree_url()))?;
+                if res.status() != StatusCode::OK {
+                    return Err(format!(
+                        "Failed to fetch types (status {})",
+                        res.status()
+                    )
+                    .into());
+                }
+                res.json()?
+            }
+        };
+        if listing.truncated {
+            note!("The template list from {} is incomplete (too many files)", self.source);
+        }
+        let mut catalog = Vec::new();
+        for entry in listing.tree {
+            if entry.kind != "blob" {
+                continue;
+            }
+            if let Some(clean) = entry.path.strip_suffix(".gitignore")
+                && !clean.is_empty()
+                && !clean.ends_with('/')
+            {
+                catalog.push(TemplateInfo {
+                    name: clean.to_string(),
+                    size: entry.size,
+                    sha: entry.sha,
+                    modified: None,
+                });
+            }
+        }
+        names::sort_and_dedup(&mut catalog, |info| &info.name);
+        Ok(catalog)
+    }
+
+    /// Fills in last-modified dates (one API call per template) and caches them.
+    fn fill_modified(&self, catalog: &mut [TemplateInfo], failures: &mut Failures) {
+        let mut changed = false;
+        let missing = catalog.iter().filter(|info| info.modified.is_none()).count();
+        let bar = progress::bar(missing, "Dating");
+        for info in catalog.iter_mut().filter(|info| info.modified.is_none()) {
+            let name = info.name.clone();
+            bar.set_message(name.clone());
+            info.modified = failures.capture(name.as_str(), || self.last_modified(&name));
+            changed |= info.modified.is_some();
+            bar.inc(1);
+        }
+        if changed && self.fixtures.is_none() {
+            let _ = write_cached_types(&self.source, catalog);
+        }
+    }
+
+    /// A catalog entry's description, from what is already on disk: the sources of
+    /// `local_description`, then the blob cache, which survives catalog refreshes as long
+    /// as the template is unchanged. Never downloads.
+    fn describe(&self, info: &TemplateInfo) -> Option<String> {
+        if let Some(description) = self.local_description(&info.name) {
+            return Some(description);
+        }
+        let content = match &self.fixtures {
+            // Fixture files stand in for the cache and are just as cheap to read.
+            Some(_) => self.download_from(&self.source, &info.name).ok().flatten()?,
+            None => read_cached_blob(&info.sha)?,
+        };
+        listing::description(&content)
+    }
+
+    /// `name`'s description from what is already on disk: a snippet, a local override
+    /// or the template cache. Never downloads.
+    fn local_description(&self, name: &str) -> Option<String> {
+        let content = match self.snippets.get(name) {
+            Some(snippet) => snippet.ok()?.body.clone(),
+            None => match self.local_override(name) {
+                Ok(Some(content)) => content,
+                _ => read_cached_template(&self.source, name)?,
+            },
+        };
+        listing::description(&content)
+    }
+
+    fn last_modified(&self, name: &str) -> Result<String, DynError> {
+        let commits: Vec<CommitEntry> = match &self.fixtures {
+            Some(dir) => {
+                let path = dir.join(FIXTURE_COMMITS_DIR).join(format!("{name}.gitignore.json"));
+                serde_json::from_str(&fs::read_to_string(path)?)?
+            }
+            None => {
+                let url = self.source.commits_url(&format!("{name}.gitignore"));
+                let res = self.send(self.api_get(&url))?;
+                if res.status() != StatusCode::OK {
+                    return Err(format!("Failed to fetch history (status {})", res.status()).into());
+                }
+                res.json()?
+            }
+        };
+        let date = &commits.first().ok_or("No commit history")?.commit.committer.date;
+        Ok(date.chars().take(10).collect())
+    }
+
+    fn not_found(&self, name: &str, status: StatusCode) -> String {
+        let mut message = format!("Template '{name}' not found (status {status})");
+        if status == StatusCode::NOT_FOUND {
+            let suggestions = names::closest(name, &self.known_types());
+            if !suggestions.is_empty() {
+                message.push_str(&format!("; did you mean {}?", suggestions.join(", ")));
+            }
+        }
+        message
+    }
+
+    /// Best-effort type list for suggestions: any cached copy, however old.
+    fn known_types(&self) -> Vec<String> {
+        let cached = match self.fixtures {
+            Some(_) => None,
+            None => read_cached_types(&self.source, Duration::MAX).ok().flatten(),
+        };
+        cached
+            .or_else(|| self.fetch_catalog().ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|info| info.name)
+            .collect()
+    }
+
+    /// Whether `name`'s content is in the user cache (never, in fixture mode).
+    fn is_cached(&self, name: &str) -> bool {
+        self.fixtures.is_none()
+            && template_cache_path(&self.source, name).is_ok_and(|path| path.exists())
+    }
+
+    fn catalog_fetched_at(&self) -> Option<u64> {
+        if self.fixtures.is_some() {
+            return None;
+        }
+        let _lock = cache::lock_shared(&cache_dir().ok()?).ok()?;
+        let contents = fs::read_to_string(cache_file_path().ok()?).ok()?;
+        let cached: CachedTypes = serde_json::from_str(&contents).ok()?;
+        (cached.source == self.source.to_string()).then_some(cached.fetched_at)
+    }
+
+    /// Sends `request`, reporting the URL with -v and the response status with -vv.
+    fn send(&self, request: RequestBuilder) -> Result<Response, DynError> {
+        let request = request.build()?;
+        let url = request.url().clone();
+        if self.locked {
+            return Err(format!("Not fetching {url} (--locked only reads the cache)").into());
+        }
+        info!(%url, "GET");
+        let res = self.client.execute(request)?;
+        debug!(%url, status = %res.status(), "response");
+        Ok(res)
+    }
+
+    /// `doctor`: whether `request`'s host answers, and with what.
+    fn probe(&self, name: &'static str, request: RequestBuilder) -> doctor::Check {
+        match self.send(request) {
+            Ok(res) if res.status().is_success() => {
+                let mut detail = format!("{} answered {}", res.url(), res.status());
+                if let Some(left) = res.headers().get("x-ratelimit-remaining") {
+                    let left = left.to_str().unwrap_or("?");
+                    detail.push_str(&format!(" ({left} API calls left this hour)"));
+                }
+                doctor::Check::ok(name, detail)
+            }
+            Ok(res) => {
+                let detail = format!("{} answered {}", res.url(), res.status());
+                doctor::Check::problem(name, detail, doctor::http_hint(res.status().as_u16()))
+            }
+            Err(err) => {
+                let hint = "check the network connection and the proxy settings below";
+                doctor::Check::problem(name, err.to_string(), hint)
+            }
+        }
+    }
+
+    /// GitHub API request, authenticated when set to lift rate limits: with `GITHUB_TOKEN`
+    /// for github.com, `GH_ENTERPRISE_TOKEN` for any other host.
+    fn api_get(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+        match env::var(source::token_var(url)) {
+            Ok(token) if !token.is_empty() => request.bearer_auth(token),
+            _ => request,
+        }
+    }
+
+    fn fetch_all(&self, types: &[String], failures: &mut Failures) -> Vec<Template> {
+        // Partial names may need a prompt, which shouldn't fight the progress bar.
+        let types: Vec<String> = types
+            .iter()
+            .filter_map(|t| failures.capture(t.as_str(), || self.canonical_name(t)))
+            .collect();
+        let bar = progress::bar(types.len(), "Fetching");
+        types
+            .iter()
+            .filter_map(|t| {
+                bar.set_message(t.clone());
+                let template = failures.capture(t.as_str(), || self.fetch(t));
+                bar.inc(1);
+                template
+            })
+            .collect()
+    }
+
+    fn fetch(&self, name: &str) -> Result<Template, DynError> {
+        let name = self.canonical_name(name)?;
+        Ok(Template {
+            content: self.content(&name, &mut Vec::new())?,
+            name,
+            provenance: None,
+        })
+    }
+
+    /// `name` spelled as upstream spells it (`cmake` → `CMake`, `visual-studio` →
+    /// `VisualStudio`), matched against the template list (see `names::canonical`).
+    /// Otherwise a name that starts or fuzzily matches a single template stands for it
+    /// (`jet` → `Global/JetBrains`), and several candidates are offered to choose from.
+    /// Snippets, the stdin template, local overrides, paths and names matching nothing stay
+    /// as typed; with no list to match against (offline, say), names get a capital first.
+    fn canonical_name(&self, name: &str) -> Result<String, DynError> {
+        let piped = self.piped.as_ref().is_some_and(|(piped, _)| names::eq_folded(piped, name));
+        if piped || self.snippets.get(name).is_some() {
+            return Ok(name.to_string());
+        }
+        let known = self.catalog_names.get_or_init(|| {
+            let cached = self.load_types(false, Duration::MAX);
+            cached.unwrap_or_else(|err| {
+                info!(error = %err, "no template list to match names against");
+                Vec::new()
+            })
+        });
+        if known.is_empty() {
+            return Ok(names::capitalize(name));
+        }
+        if let Some(canonical) = names::canonical(name, known) {
+            return Ok(canonical.to_string());
+        }
+        let overridden = self.overrides.join(format!("{name}.gitignore")).exists();
+        if overridden || name.contains('/') {
+            return Ok(name.to_string());
+        }
+        let candidates = names::partial_matches(name, known);
+        match candidates.as_slice() {
+            [] => Ok(name.to_string()),
+            [only] => {
+                note!("Using {only} for `{name}`");
+                Ok(only.to_string())
+            }
+            several => choose_template(name, several).map(str::to_string),
+        }
+    }
+
+    /// Template or flattened snippet content with rules applied; `stack` tracks includes.
+    fn content(&self, name: &str, stack: &mut Vec<String>) -> Result<String, DynError> {
+        let piped = self.piped.as_ref().filter(|(piped, _)| names::eq_folded(piped, name));
+        let content = match (piped, self.snippets.get(name)) {
+            (Some((_, content)), _) => content.clone(),
+            (None, Some(snippet)) => self.flatten_snippet(name, snippet?, stack)?,
+            (None, None) => self.download(&self.canonical_name(name)?)?,
+        };
+        Ok(match self.rules.get(name) {
+            Some(rules) => rules.apply(&content),
+            None => content,
+        })
+    }
+
+    /// Emits a snippet's includes, in order, ahead of its own body.
+    fn flatten_snippet(
+        &self,
+        name: &str,
+        snippet: &Snippet,
+        stack: &mut Vec<String>,
+    ) -> Result<String, DynError> {
+        if stack.iter().any(|seen| names::eq_folded(seen, name)) {
+            return Err(format!("Snippet include cycle: {} -> {name}", stack.join(" -> ")).into());
+        }
+        stack.push(name.to_string());
+        let mut parts = Vec::with_capacity(snippet.include.len() + 1);
+        for entry in &snippet.include {
+            parts.push(self.content(&snippets::include_target(entry), stack)?);
+        }
+        stack.pop();
+        parts.push(snippet.body.clone());
+
+        let parts: Vec<String> = parts
+            .iter()
+            .map(|part| section_body(part))
+            .filter(|part| !part.is_empty())
+            .collect();
+        Ok(parts.join("\n"))
+    }
+
+    /// Like `content`, but preferring the cached copy of a template.
+    fn cached_content(&self, name: &str) -> Result<String, DynError> {
+        if self.snippets.get(name).is_some() {
+            return self.content(name, &mut Vec::new());
+        }
+        let content = self.cached_or_download(name)?;
+        Ok(match self.rules.get(name) {
+            Some(rules) => rules.apply(&content),
+            None => content,
+        })
+    }
+
+    /// Raw upstream content, from the local template cache when available.
+    fn cached_or_download(&self, name: &str) -> Result<String, DynError> {
+        if let Some(content) = self.local_override(name)? {
+            return Ok(content);
+        }
+        if self.fixtures.is_none()
+            && let Some(content) = read_cached_template(&self.source, name)
+        {
+            info!(template = name, "using cached copy");
+            return Ok(content);
+        }
+        self.download(name)
+    }
+
+    /// The user's own copy of `name` from the overrides directory, if there is one.
+    fn local_override(&self, name: &str) -> Result<Option<String>, DynError> {
+        let path = self.overrides.join(format!("{name}.gitignore"));
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                info!(template = name, path = %path.display(), "using local override");
+                let origin = Origin { url: path.display().to_string(), source: None };
+                self.origins.borrow_mut().insert(name.to_string(), origin);
+                Ok(Some(content))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("Cannot read {}: {err}", path.display()).into()),
+        }
+    }
+
+    /// Whether a `name` section holds patterns `gi` wrote from something local (`suggest`,
+    /// `import-hg`, stdin or an override) rather than an upstream template.
+    fn is_local_section(&self, name: &str) -> bool {
+        let piped = self.piped.as_ref().is_some_and(|(piped, _)| names::eq_folded(piped, name));
+        piped
+            || [LOCAL_SECTION, IMPORTED_SECTION, STDIN_TEMPLATE]
+                .iter()
+                .any(|local| names::eq_folded(local, name))
+            || self.overrides.join(format!("{name}.gitignore")).exists()
+    }
+
+    /// What `gi` wrote in `section`, from local copies only (snippets, overrides, the
+    /// template cache or fixtures), both as fetched and as its transforms left it so
+    /// either form of a line counts; `None` when one of its templates isn't at hand.
+    fn written_locally(&self, section: &Section) -> Option<String> {
+        let template = |name: &str| {
+            let content = match self.snippets.get(name) {
+                Some(snippet) => snippet.ok()?.body.clone(),
+                None => match self.local_override(name) {
+                    Ok(Some(content)) => content,
+                    _ if self.fixtures.is_some() => self.download(name).ok()?,
+                    _ => read_cached_template(&self.source, name)?,
+                },
+            };
+            Some(match self.rules.get(name) {
+                Some(rules) => rules.apply(&content),
+                None => content,
+            })
+        };
+        let transforms = Transforms::parse(section.written_with());
+        let content = if transforms.dedupe_lines {
+            let parts: Option<Vec<(&str, String)>> = tidy::merged_parts(&section.name)
+                .into_iter()
+                .map(|part| Some((part, template(part)?)))
+                .collect();
+            tidy::merge(parts?.iter().map(|(name, content)| (*name, content.as_str())))
+        } else {
+            template(&section.name)?
+        };
+        Some(format!("{content}\n{}", transforms.apply(&content)))
+    }
+
+    /// Downloads `name` from the first source that has it: `source`, then the fallbacks,
+    /// then the bundle. A local override wins over all of them, and skips `--verify`.
+    fn download(&self, name: &str) -> Result<String, DynError> {
+        if let Some(content) = self.local_override(name)? {
+            return Ok(content);
+        }
+        let pinned = self.pins.iter().find(|(pinned, _)| names::eq_folded(pinned, name)).map(
+            |(_, reference)| Source { reference: reference.clone(), ..self.source.clone() },
+        );
+        // A pinned template comes from its ref or not at all.
+        let sources: Vec<&Source> = match &pinned {
+            Some(pinned) => vec![pinned],
+            None => std::iter::once(&self.source).chain(&self.fallbacks).collect(),
+        };
+        for (index, &source) in sources.iter().enumerate() {
+            if let Some(content) = self.download_from(source, name)? {
+                info!(template = name, %source, "resolved");
+                let origin = Origin { url: source.raw_url(name), source: Some(source.clone()) };
+                self.origins.borrow_mut().insert(name.to_string(), origin);
+                if index > 0 {
+                    note!("{name}: not in {}, using {source}", self.source);
+                }
+                return self.verified(name, content);
+            }
+        }
+        if pinned.is_none()
+            && let Some(bundle) = &self.bundle
+            && let Some((path, content)) = self.bundled(bundle, &sources, name)?
+        {
+            info!(template = name, bundle = %bundle.display(), "resolved from the bundle");
+            let url = format!("{}:{}", bundle.display(), path.display());
+            self.origins.borrow_mut().insert(name.to_string(), Origin { url, source: None });
+            note!("{name}: not in {}, using {}", self.source, bundle.display());
+            return self.verified(name, content);
+        }
+        if self.locked {
+            return Err(format!("{name} is not in the cache (--locked doesn't download)").into());
+        }
+        Err(self.not_found(name, StatusCode::NOT_FOUND).into())
+    }
+
+    /// `name` from `bundle` and its path there, preferring the copy cached from the first
+    /// of `sources` over copies from other sources or refs.
+    fn bundled(
+        &self,
+        bundle: &Path,
+        sources: &[&Source],
+        name: &str,
+    ) -> Result<Option<(PathBuf, String)>, DynError> {
+        let base = Path::new(TEMPLATE_CACHE_DIR);
+        let templates = match self.bundled.get() {
+            Some(templates) => templates,
+            None => {
+                let read = cache::read_bundle(bundle, base)
+                    .map_err(|err| format!("Cannot read {}: {err}", bundle.display()))?;
+                self.bundled.get_or_init(|| read)
+            }
+        };
+        let file = format!("{name}.gitignore");
+        let preferred = sources.iter().map(|source| source_cache_dir(base, source).join(&file));
+        let path = preferred.into_iter().find(|path| templates.contains_key(path)).or_else(|| {
+            let mut others: Vec<&PathBuf> =
+                templates.keys().filter(|path| path.ends_with(&file)).collect();
+            others.sort();
+            others.first().map(|path| path.to_path_buf())
+        });
+        Ok(path.map(|path| {
+            let content = templates[&path].clone();
+            (path, content)
+        }))
+    }
+
+    /// `name`'s content from `source`, or `None` if it doesn't have it.
+    fn download_from(&self, source: &Source, name: &str) -> Result<Option<String>, DynError> {
+        if let Some(dir) = &self.fixtures {
+            // Files from hosts other than github.com sit under `hosts/<cache dir name>/`.
+            let dir = if source.hosts.is_github() {
+                dir.clone()
+            } else {
+                dir.join(FIXTURE_HOSTS_DIR).join(source.hosts.cache_dir_name())
+            };
+            let path = dir.join(FIXTURE_RAW_DIR).join(format!("{name}.gitignore"));
+            info!(path = %path.display(), "reading fixture");
+            return Ok(fs::read_to_string(path).ok());
+        }
+        if !self.no_cache && recently_missing(source, name) {
+            info!(template = name, %source, "not found a moment ago; not asking again");
+            return Ok(None);
+        }
+        let blob = self.blob_sha(source, name).and_then(|sha| read_cached_blob(&sha));
+        if let Some(content) = blob {
+            info!(template = name, "same content already cached; not downloading");
+            let _ = write_cached_template(source, name, &content, self.cache_max_size);
+            return Ok(Some(content));
+        }
+        if self.locked {
+            return Ok(read_cached_template(source, name));
+        }
+        let res = self.send(self.client.get(source.raw_url(name)))?;
+        if res.status() == StatusCode::NOT_FOUND {
+            let _ = record_missing(source, name);
+            return Ok(None);
+        }
+        if res.status() != StatusCode::OK {
+            return Err(self.not_found(name, res.status()).into());
+        }
+        let content = res.text()?;
+        // Best effort: the copy only feeds previews and `grep`.
+        let _ = write_cached_template(source, name, &content, self.cache_max_size);
+        let _ = write_cached_blob(&content, self.cache_max_size);
+        Ok(Some(content))
+    }
+
+    /// `--provenance` comment lines for `name`: where it came from, the commit its source's
+    /// ref pointed at, and when. `None` for snippets.
+    fn provenance(&self, name: &str, fetched_at: u64) -> Option<String> {
+        let origin = self.origins.borrow().get(name).cloned()?;
+        let mut lines = format!("# Source: {}\n", origin.url);
+        if let Some(commit) = origin.source.and_then(|source| self.commit_sha(&source)) {
+            lines.push_str(&format!("# Commit: {commit}\n"));
+        }
+        lines.push_str(&format!("# Fetched: {}\n", backup::utc_timestamp(fetched_at)));
+        Some(lines)
+    }
+
+    fn commit_sha(&self, source: &Source) -> Option<String> {
+        if self.fixtures.is_some() {
+            return None;
+        }
+        let key = source.to_string();
+        if let Some(known) = self.commits.borrow().get(&key) {
+            return known.clone();
+        }
+        let request = self
+            .api_get(&source.commit_url())
+            .header(reqwest::header::ACCEPT, "application/vnd.github.sha");
+        let sha = self
+            .send(request)
+            .ok()
+            .filter(|res| res.status().is_success())
+            .and_then(|res| res.text().ok())
+            .map(|sha| sha.trim().to_string())
+            .filter(|sha| !sha.is_empty());
+        self.commits.borrow_mut().insert(key, sha.clone());
+        sha
+    }
+
+    fn verified(&self, name: &str, content: String) -> Result<String, DynError> {
+        if let Some(manifest) = &self.manifest {
+            manifest.verify(name, &content)?;
+        }
+        Ok(content)
+    }
+
+    /// `name`'s blob SHA according to the cached catalog, however old: a stale entry only
+    /// means a blob that isn't cached, never wrong content.
+    fn blob_sha(&self, source: &Source, name: &str) -> Option<String> {
+        let mut known = self.blob_shas.borrow_mut();
+        let shas = known.entry(source.to_string()).or_insert_with(|| {
+            let catalog = read_cached_types(source, Duration::MAX).ok().flatten();
+            let catalog = catalog.unwrap_or_default().into_iter();
+            catalog.map(|info| (info.name, info.sha)).collect()
+        });
+        shas.get(name).cloned()
+    }
+}
+
+/// Before clobbering a non-empty file at a terminal, shows what would change and asks.
+/// Scripts (no terminal) and `--yes` keep the old unprompted behavior.
+fn confirm_overwrite(
+    output: &Path,
+    existing: &str,
+    replacement: &str,
+    options: &WriteOptions,
+) -> Result<bool, DynError> {
+    if existing.trim().is_empty() || options.assume_yes || !is_interactive() {
+        return Ok(true);
+    }
+    let label = output.display().to_string();
+    let Some(diff) = unified_diff(existing, replacement, &label, &format!("{label} (new)")) else {
+        return Ok(true);
+    };
+    let lost = removed_lines(existing, replacement);
+    eprint!("{}", colorize_diff(&diff, true));
+    confirm(&format!("Overwrite {label}, dropping {lost} existing line(s)?"), false)
+}
+
+/// `--diff`: prints the pending change and says whether to go ahead and write it.
+fn preview_changes(
+    output: &Path,
+    existing: &str,
+    rendered: &str,
+    options: &WriteOptions,
+) -> Result<bool, DynError> {
+    let label = output.display().to_string();
+    let Some(diff) = unified_diff(existing, rendered, &label, &format!("{label} (new)")) else {
+        status!("No changes to {label}");
+        return Ok(false);
+    };
+    let colored = colorize_diff(&diff, false);
+    if options.dry_run {
+        // Nothing to confirm afterwards, so the diff may go through the pager.
+        pager::print(&colored)?;
+    } else {
+        print!("{colored}");
+    }
+    for finding in lint::shadowed(rendered) {
+        note!("{label}:{}: {}", finding.line, finding.message);
+    }
+    if options.dry_run {
+        return Ok(false);
+    }
+    if options.assume_yes || !is_interactive() {
+        return Ok(true);
+    }
+    confirm(&format!("Apply these changes to {label}?"), false)
+}
+
+/// One template as written: header, content, and a trailing blank line.
+fn render_section(tpl: &Template) -> String {
+    let provenance = tpl.provenance.as_deref().unwrap_or_default();
+    let mut section = format!("{}\n{provenance}{}", section_header(&tpl.name), tpl.content);
+    if !tpl.content.ends_with('\n') {
+        section.push('\n');
+    }
+    section.push('\n');
+    section
+}
+
+/// The output after applying `templates`, plus the names actually added. Appending keeps
+/// `existing` and puts templates at `anchor`, skipping those that already have a section,
+/// or whose content was pasted in by hand; overwriting starts over.
+fn render_output<'a>(
+    existing: &str,
+    overwrite: bool,
+    anchor: &Anchor,
+    templates: &'a [Template],
+) -> Result<(String, Vec<&'a str>), DynError> {
+    let mut document = if overwrite {
+        Document::default()
+    } else {
+        Document::parse(existing)
+    };
+    let mut added = Vec::new();
+    let mut position = None;
+    for tpl in templates {
+        if document.section(&tpl.name).is_some() || document.has_unmanaged(&tpl.content) {
+            continue;
+        }
+        let index = match position {
+            Some(index) => index,
+            None => document.position(anchor).ok_or_else(|| match anchor {
+                Anchor::Before(marker) | Anchor::After(marker) => {
+                    format!("No section or line `{marker}` to insert next to")
+                }
+                Anchor::End | Anchor::Top => unreachable!("always found"),
+            })?,
+        };
+        document.insert(index, tpl);
+        position = Some(index + 1);
+        added.push(tpl.name.as_str());
+    }
+    Ok((document.render(), added))
+}
+
+fn write_templates(
+    output: &Path,
+    options: &WriteOptions,
+    templates: &[Template],
+) -> Result<(), DynError> {
+    if output == Path::new(STDOUT) {
+        // Exactly what a fresh file would get, so `gi -o - rust > x` matches `gi -o x rust`.
+        let rendered = render_output("", true, &Anchor::End, templates)?.0;
+        match options.line_ending {
+            Some(ending) => print!("{}", line_endings::convert(&rendered, ending)),
+            None => print!("{rendered}"),
+        }
+        return Ok(());
+    }
+    if options.dry_run && !options.diff {
+        let sections: String = templates.iter().map(render_section).collect();
+        return pager::print(&sections);
+    }
+
+    let existed = output.exists();
+    let existing = read_output(output)?;
+    let (rendered, added) =
+        render_output(&existing, options.overwrite, &options.anchor, templates)
+            .map_err(|err| format!("{}: {err}", output.display()))?;
+    if !options.overwrite {
+        for tpl in templates.iter().filter(|tpl| !added.contains(&tpl.name.as_str())) {
+            note!("Skipping {} (already present)", tpl.name);
+        }
+        if added.is_empty() {
+            return Ok(());
+        }
+    }
+
+    let rendered = if options.edit {
+        let edited = editor::edit(&rendered, output)
+            .map_err(|err| format!("{err}; left {} unchanged", output.display()))?;
+        if edited.trim().is_empty() {
+            note!("Empty result; left {} unchanged", output.display());
+            return Ok(());
+        }
+        edited
+    } else {
+        rendered
+    };
+
+    let proceed = if options.diff {
+        preview_changes(output, &existing, &rendered, options)?
+    } else if options.overwrite && !options.edit {
+        // With --edit the user has already seen the result.
+        confirm_overwrite(output, &existing, &rendered, options)?
+    } else {
+        true
+    };
+    if !proceed {
+        if !options.dry_run && rendered != existing {
+            note!("Left {} unchanged", output.display());
+        }
+        return Ok(());
+    }
+
+    let names = added.iter().map(|name| name.to_string()).collect();
+    replace_file(output, existed.then_some(existing.as_str()), &rendered, names, options)?;
+    if options.overwrite {
+        status!("Wrote templates to {}", output.display());
+    } else {
+        added.iter().for_each(|name| status!("Appended {name}"));
+    }
+    Ok(())
+}
+
+/// Prints `file` with each line labelled by the section whose template contains it.
+/// Lines outside sections, or hand-added inside one, are "unmanaged"; `Name?` marks
+/// sections whose template couldn't be loaded to check.
+fn explain_file(fetcher: &Fetcher, file: &Path) -> Result<(), DynError> {
+    let content = fs::read_to_string(file)
+        .map_err(|err| format!("Cannot read {}: {err}", file.display()))?;
+    let mut known: BTreeMap<String, Option<HashSet<String>>> = BTreeMap::new();
+    let mut section: Option<&str> = None;
+    let mut rows: Vec<(String, &str)> = Vec::new();
+    for line in content.lines() {
+        if let Some(name) = parse_section_header(line) {
+            section = Some(name);
+            known.entry(name.to_string()).or_insert_with(|| {
+                match fetcher.cached_content(name) {
+                    Ok(template) => Some(template.lines().map(|l| l.trim().to_string()).collect()),
+                    Err(err) => {
+                        note!("Cannot load {name} to check its lines: {err}");
+                        None
+                    }
+                }
+            });
+            rows.push((name.to_string(), line));
+            continue;
+        }
+        if let Some(name) = section
+            && parse_section_end(line).is_some_and(|end| names::eq_folded(end, name))
+        {
+            section = None;
+            rows.push((name.to_string(), line));
+            continue;
+        }
+        let label = match section.map(|name| (name, &known[name])) {
+            _ if line.trim().is_empty() => String::new(),
+            Some((name, Some(lines))) if lines.contains(line.trim()) => name.to_string(),
+            Some((name, None)) => format!("{name}?"),
+            _ => "unmanaged".to_string(),
+        };
+        rows.push((label, line));
+    }
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    for (label, line) in rows {
+        if line.is_empty() {
+            println!();
+        } else {
+            println!("{}  {line}", style(format!("{label:<width$}")).dim());
+        }
+    }
+    Ok(())
+}
+
+/// `self-update`: installs the latest release over the running binary, refusing any
+/// artifact without a published checksum that matches.
+fn self_update(fetcher: &Fetcher, check_only: bool) -> Result<(), DynError> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release: update::Release = match &fetcher.fixtures {
+        Some(dir) => serde_json::from_str(&fs::read_to_string(dir.join(FIXTURE_RELEASE_FILE))?)?,
+        None => {
+            let res = fetcher.send(fetcher.api_get(&update::latest_release_url()))?;
+            if !res.status().is_success() {
+                let status = res.status();
+                return Err(format!("Cannot look up the latest release (status {status})").into());
+            }
+            res.json()?
+        }
+    };
+    let latest = release.version();
+    if !update::is_newer(latest, current) {
+        status!("gi {current} is the latest release");
+        return Ok(());
+    }
+    if check_only {
+        status!("gi {latest} is available (you have {current}); run `gi self-update`");
+        return Ok(());
+    }
+
+    let artifact = update::artifact_name();
+    let tag = &release.tag_name;
+    let asset = release
+        .asset(&artifact)
+        .ok_or_else(|| format!("Release {tag} has no build for this platform ({artifact})"))?;
+    let checksums = release
+        .asset(&format!("{artifact}.sha256"))
+        .or_else(|| release.asset("SHA256SUMS"))
+        .ok_or_else(|| format!("Release {tag} publishes no checksums; not installing it"))?;
+    let listing = String::from_utf8(download_asset(fetcher, checksums)?)?;
+    let expected = update::expected_sha256(&listing, &artifact)
+        .ok_or_else(|| format!("{} has no checksum for {artifact}", checksums.name))?;
+
+    let bytes = {
+        let _spinner = progress::spinner(&format!("Downloading gi {latest}"));
+        download_asset(fetcher, asset)?
+    };
+    let actual = verify::sha256_hex(&bytes);
+    if actual != expected {
+        let mismatch = format!("expected {expected}, got {actual}");
+        let message = format!("Checksum mismatch for {artifact} ({mismatch})");
+        return Err(format!("{message}; not installing it").into());
+    }
+    let exe = env::current_exe()?;
+    replace_executable(&exe, &bytes)?;
+    status!("Updated gi {current} -> {latest} ({})", exe.display());
+    Ok(())
+}
+
+/// A release asset's bytes; under test, the file of that name in the fixtures.
+fn download_asset(fetcher: &Fetcher, asset: &update::Asset) -> Result<Vec<u8>, DynError> {
+    if let Some(dir) = &fetcher.fixtures {
+        let path = dir.join(FIXTURE_ASSETS_DIR).join(&asset.name);
+        let bytes = fs::read(&path).map_err(|err| format!("Cannot read {}: {err}", path.display()));
+        return Ok(bytes?);
+    }
+    let res = fetcher.send(fetcher.client.get(&asset.browser_download_url))?;
+    Ok(res.error_for_status()?.bytes()?.to_vec())
+}
+
+fn replace_executable(exe: &Path, bytes: &[u8]) -> Result<(), DynError> {
+    // Windows can't replace a running executable, but it can rename one out of the way.
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)?;
+    }
+    // Keeps the executable's permissions, and never leaves a half-written binary behind.
+    atomic::write(exe, bytes)
+}
+
+/// Prints a one-line hint when a newer release is out, asking GitHub at most once a day.
+/// Stays silent for scripts (no terminal on stderr) and on any failure.
+fn notify_new_release(fetcher: &Fetcher) {
+    if fetcher.fixtures.is_some() || !io::stderr().is_terminal() {
+        return;
+    }
+    let current = env!("CARGO_PKG_VERSION");
+    let Ok(path) = cache_dir().map(|dir| dir.join(RELEASE_CHECK_FILE)) else {
+        return;
+    };
+    let last = fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<update::LastCheck>(&contents).ok());
+    let last = match last {
+        Some(last) if !last.is_due() => last,
+        _ => {
+            // Short timeout: this must never hold up the command itself.
+            let request = fetcher.api_get(&update::latest_release_url());
+            let request = request.timeout(Duration::from_secs(2));
+            let latest = fetcher
+                .send(request)
+                .ok()
+                .filter(|res| res.status().is_success())
+                .and_then(|res| res.json::<update::Release>().ok())
+                .map_or_else(|| current.to_string(), |release| release.version().to_string());
+            let check = update::LastCheck::now(&latest);
+            if let Ok(serialized) = serde_json::to_string(&check)
+                && let Ok(dir) = cache_dir()
+                && let Ok(_lock) = cache::lock_exclusive(&dir)
+            {
+                let _ = atomic::write(&path, serialized.as_bytes());
+            }
+            check
+        }
+    };
+    if update::is_newer(&last.latest, current) {
+        note!(
+            "gi {} is available (you have {current}); run `gi self-update` to install it",
+            last.latest
+        );
+    }
+}
+
+/// `doctor`: runs every check, then fails if any found a problem.
+fn run_doctor(fetcher: &Fetcher, outputs: &[PathBuf]) -> Result<(), DynError> {
+    let mut checks = Vec::new();
+    if fetcher.fixtures.is_some() {
+        checks.push(doctor::Check::skipped("api", "fixture mode"));
+        checks.push(doctor::Check::skipped("raw", "fixture mode"));
+    } else {
+        checks.push(fetcher.probe("api", fetcher.api_get(&fetcher.source.tree_url())));
+        checks.push(match fetcher.known_types().first() {
+            Some(name) => fetcher.probe("raw", fetcher.client.get(fetcher.source.raw_url(name))),
+            None => doctor::Check::skipped("raw", "no template list to pick a file from"),
+        });
+    }
+    let var = |name: &str| env::var(name).ok();
+    checks.push(doctor::proxy(var));
+    checks.push(doctor::tls(var));
+    checks.push(check_cache()?);
+    for output in outputs.iter().filter(|path| *path != Path::new(STDOUT)) {
+        checks.push(doctor::writable(output));
+    }
+    match doctor::report(&checks) {
+        0 => {
+            status!("No problems found");
+            Ok(())
+        }
+        problems => Err(format!("{problems} problem(s) found").into()),
+    }
+}
+
+/// `doctor`: the cache is readable, and cached blobs still match their SHA.
+fn check_cache() -> Result<doctor::Check, DynError> {
+    let dir = cache_dir()?;
+    let entries = {
+        let _lock = cache::lock_shared(&dir)?;
+        cache::entries(&dir)?
+    };
+    let types_file = cache_file_path()?;
+    if let Ok(contents) = fs::read_to_string(&types_file)
+        && serde_json::from_str::<CachedTypes>(&contents).is_err()
+    {
+        let detail = format!("{} is unreadable", types_file.display());
+        return Ok(doctor::Check::problem("cache", detail, "run `gi cache clear --types`"));
+    }
+    let corrupt = entries
+        .iter()
+        .filter(|entry| entry.path.starts_with(BLOB_CACHE_DIR))
+        .filter(|entry| {
+            let sha: String = entry.path.iter().skip(1).map(|p| p.to_string_lossy()).collect();
+            let content = fs::read(dir.join(&entry.path)).unwrap_or_default();
+            verify::git_blob_sha(&content) != sha
+        })
+        .count();
+    if corrupt > 0 {
+        let detail = format!("{corrupt} cached template(s) don't match their SHA");
+        return Ok(doctor::Check::problem("cache", detail, "run `gi cache clear`"));
+    }
+    let total = entries.iter().map(|entry| entry.size).sum();
+    let detail = format!(
+        "{} file(s), {} in {}",
+        entries.len(),
+        cache::format_size(total),
+        dir.display()
+    );
+    Ok(doctor::Check::ok("cache", detail))
+}
+
+/// `hook install`: points the hook at `output` relative to the work tree, where git runs it.
+fn install_hook(output: &Path, kind: HookKind, force: bool) -> Result<(), DynError> {
+    let root = git::repo_root(Path::new(".")).ok_or("Not inside a git repository")?;
+    let absolute = std::path::absolute(output)?;
+    let relative = absolute.strip_prefix(&root).map_err(|_| {
+        format!("{} is outside the repository at {}", output.display(), root.display())
+    })?;
+    let exe = env::current_exe()?;
+    let script = hooks::script(&exe, relative);
+    let path = hooks::install(&git::hooks_dir(Path::new("."))?, kind, &script, force)?;
+    status!("Installed {}", path.display());
+    Ok(())
+}
+
+/// `init`: starts from what detection finds plus the configured `templates`, and lets the
+/// user adjust that in the picker, where profiles are offered as `@name`.
+fn init_selection(
+    fetcher: &Fetcher,
+    table: &DetectionTable,
+    configured: &[String],
+    types: &[String],
+    profiles: &BTreeMap<String, Vec<String>>,
+    options: &WriteOptions,
+) -> Result<Vec<String>, DynError> {
+    let mut suggested: Vec<String> = Vec::new();
+    for detection in detect_templates(Path::new("."), table) {
+        status!("Detected {} (found {})", detection.template, detection.evidence);
+        suggested.push(detection.template);
+    }
+    for name in configured {
+        if !suggested.iter().any(|t| names::eq_folded(t, name)) {
+            suggested.push(name.clone());
+        }
+    }
+    if options.assume_yes || !is_interactive() {
+        if suggested.is_empty() {
+            return Err(NothingDetected::new(Path::new(".")).into());
+        }
+        return Ok(suggested);
+    }
+    // Preselect by catalog spelling so detected names line up with picker rows.
+    let preselected: Vec<String> = suggested
+        .iter()
+        .map(|name| {
+            let known = types.iter().find(|t| names::eq_folded(t, name));
+            known.unwrap_or(name).clone()
+        })
+        .collect();
+    let mut items: Vec<String> = profiles.keys().map(|name| format!("@{name}")).collect();
+    items.extend(types.iter().cloned());
+    prompt_for_types(fetcher, &items, &preselected, &ListFilter::default())
+}
+
+/// `init.templateDir` set by `install-git-template` when there is none, under the home dir.
+const DEFAULT_GIT_TEMPLATE_DIR: &str = ".git-templates";
+
+/// Section holding hand-picked patterns from `suggest`.
+const LOCAL_SECTION: &str = "Local";
+/// Section holding the rules `import-hg` converted.
+const IMPORTED_SECTION: &str = "Imported";
+/// Untracked paths shown per suggestion before eliding the rest.
+const SUGGEST_PREVIEW: usize = 3;
+
+/// Uncached templates `suggest` may download, so a cold cache doesn't mean fetching the
+/// whole catalog.
+const SUGGEST_DOWNLOADS: usize = 10;
+
+/// The templates `suggest` ranks, in catalog order: every one already cached, plus up to
+/// `SUGGEST_DOWNLOADS` others, those detected in the current directory first and then
+/// popular ones.
+fn suggestion_candidates(
+    fetcher: &Fetcher,
+    types: &[String],
+    table: &DetectionTable,
+) -> Vec<String> {
+    let detected = detect_templates(Path::new("."), table).into_iter().map(|d| d.template);
+    let popular = detect::POPULAR_TEMPLATES.iter().map(|name| name.to_string());
+    let mut wanted: Vec<String> = Vec::new();
+    for name in detected.chain(popular) {
+        let known = types.iter().find(|t| names::eq_folded(t, &name));
+        if let Some(known) = known.filter(|t| !fetcher.is_cached(t) && !wanted.contains(t)) {
+            wanted.push(known.clone());
+        }
+    }
+    wanted.truncate(SUGGEST_DOWNLOADS);
+    types.iter().filter(|t| fetcher.is_cached(t) || wanted.contains(t)).cloned().collect()
+}
+
+/// `suggest`: ranks `types` by how many untracked paths in the repository each would ignore,
+/// then offers one-off patterns for what none of them covers. Templates that can't be read
+/// are skipped with a note.
+fn suggest_templates(
+    fetcher: &Fetcher,
+    types: &[String],
+    outputs: &[PathBuf],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let root = git::repo_root(Path::new(".")).ok_or("Not inside a git repository")?;
+    let untracked = git::untracked(&root)?;
+    if untracked.is_empty() {
+        status!("No untracked files");
+        return Ok(());
+    }
+    info!(untracked = untracked.len(), "ranking templates");
+
+    let mut ranked: Vec<(&str, Vec<&str>)> = Vec::new();
+    let bar = progress::bar(types.len(), "Checking");
+    for name in types {
+        bar.set_message(name.clone());
+        let content = fetcher.cached_content(name);
+        bar.inc(1);
+        let content = match content {
+            Ok(content) => content,
+            Err(err) => {
+                note!("Skipping {name}: {err}");
+                continue;
+            }
+        };
+        let matcher = Matcher::new(&root, &content)?;
+        let absorbed: Vec<&str> = untracked
+            .iter()
+            .filter(|path| {
+                let verdict = matcher.check(&root.join(path), path.ends_with('/'));
+                matches!(verdict, Ok(Verdict::Ignored { .. }))
+            })
+            .map(String::as_str)
+            .collect();
+        if !absorbed.is_empty() {
+            ranked.push((name, absorbed));
+        }
+    }
+    drop(bar);
+
+    // Stable, so ties keep catalog order.
+    ranked.sort_by_key(|(_, absorbed)| std::cmp::Reverse(absorbed.len()));
+    if ranked.is_empty() {
+        status!("No template covers the {} untracked path(s)", untracked.len());
+    }
+    for (name, absorbed) in &ranked {
+        let mut preview = absorbed[..absorbed.len().min(SUGGEST_PREVIEW)].join(", ");
+        if absorbed.len() > SUGGEST_PREVIEW {
+            preview.push_str(", ...");
+        }
+        println!("{name}: {} untracked ({preview})", absorbed.len());
+    }
+    let leftovers: Vec<&str> = untracked
+        .iter()
+        .map(String::as_str)
+        .filter(|path| !ranked.iter().any(|(_, absorbed)| absorbed.contains(path)))
+        .collect();
+    offer_local_patterns(&leftovers, outputs, options)
+}
+
+/// Lists a pattern for each untracked path no template covers and, at a terminal, lets the
+/// user pick some for a `Local` section.
+fn offer_local_patterns(
+    leftovers: &[&str],
+    outputs: &[PathBuf],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    if leftovers.is_empty() {
+        return Ok(());
+    }
+    let mut proposals: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for path in leftovers {
+        proposals.entry(matching::propose_pattern(path)).or_default().push(path);
+    }
+    println!("Not covered by any template:");
+    for (pattern, paths) in &proposals {
+        println!("  {pattern} ({})", paths.join(", "));
+    }
+    // Unattended runs only report: a pattern like `*.md` is a judgment call.
+    if options.assume_yes || !is_interactive() {
+        return Ok(());
+    }
+    let targets: Vec<String> = outputs.iter().map(|path| path.display().to_string()).collect();
+    let targets = targets.join(", ");
+    let prompt = format!("Add some of these to a {LOCAL_SECTION} section of {targets}?");
+    if !confirm(&prompt, false)? {
+        return Ok(());
+    }
+    let patterns: Vec<String> = proposals.keys().cloned().collect();
+    let chosen = picker::pick_many("Patterns", &patterns, &[], |_| None, |pattern| {
+        proposals.get(pattern).map(|paths| paths.join("\n"))
+    })?;
+    let local = Template {
+        name: LOCAL_SECTION.to_string(),
+        content: chosen.join("\n") + "\n",
+        provenance: None,
+    };
+    for output in outputs {
+        write_templates(output, options, slice::from_ref(&local))?;
+    }
+    Ok(())
+}
+
+/// `test`: evaluates `paths` against `output`, plus `proposed` templates if any, with git's
+/// matching rules.
+fn test_paths(
+    fetcher: &Fetcher,
+    output: &Path,
+    proposed: &[String],
+    paths: &[PathBuf],
+) -> Result<(), DynError> {
+    if proposed.is_empty() && !output.exists() {
+        let hint = "add templates with --with";
+        return Err(format!("{} does not exist ({hint})", output.display()).into());
+    }
+    let existing = read_output(output)?;
+    let mut failures = Failures::default();
+    let templates = fetcher.fetch_all(proposed, &mut failures);
+    failures.into_result()?;
+    let (content, _) = render_output(&existing, false, &Anchor::End, &templates)?;
+
+    let root = output.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let root = root.unwrap_or(Path::new("."));
+    let matcher = Matcher::new(root, &content)?;
+    for path in paths {
+        let is_dir = path.is_dir() || path.to_string_lossy().ends_with('/');
+        let verdict = match matcher.check(path, is_dir)? {
+            Verdict::Ignored { rule, line } => format!("ignored by `{rule}` (line {line})"),
+            Verdict::Included { rule, line } => format!("re-included by `{rule}` (line {line})"),
+            Verdict::NotMatched => "not ignored".to_string(),
+            Verdict::Outside => format!("outside {}", root.display()),
+        };
+        println!("{}: {verdict}", path.display());
+    }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-struct CachedTypes {
-    fetched_at: u64,
-    types: Vec<String>,
+fn lint_file(output: &Path, config: &LintConfig) -> Result<(), DynError> {
+    let content = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let findings = lint::lint(&content, config);
+    for finding in &findings {
+        let location = format!("{}:{}", output.display(), finding.line);
+        let rule = style(finding.rule).yellow();
+        println!("{}: {rule}: {}", style(location).bold(), finding.message);
+    }
+    match findings.len() {
+        0 => {
+            status!("No problems in {}", output.display());
+            Ok(())
+        }
+        count => Err(format!("{count} problem(s) in {}", output.display()).into()),
+    }
 }
 
-impl CachedTypes {
-    fn is_fresh(&self, ttl: Duration) -> bool {
-        let fetched = UNIX_EPOCH + Duration::from_secs(self.fetched_at);
-        fetched.elapsed().map(|age| age <= ttl).unwrap_or(false)
+/// `lint --fix`: shows the diff of the safe fixes and writes them (only shows with --dry-run).
+fn fix_lint(output: &Path, config: &LintConfig, options: &WriteOptions) -> Result<(), DynError> {
+    let content = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let fixed = lint::fix(&content, &lint::lint(&content, config));
+    let label = output.display().to_string();
+    let Some(diff) = unified_diff(&content, &fixed, &label, &format!("{label} (fixed)")) else {
+        return Ok(());
+    };
+    print!("{}", colorize_diff(&diff, false));
+    if options.dry_run {
+        return Ok(());
     }
+    replace_file(output, Some(&content), &fixed, vec!["lint --fix".into()], options)?;
+    status!("Fixed {label}");
+    Ok(())
 }
 
-fn main() {
-    if let Err(err) = run() {
-        eprintln!("Error: {err}");
-        std::process::exit(1);
+fn normalize_file(output: &Path, options: &WriteOptions) -> Result<(), DynError> {
+    let existing = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let normalized = tidy::normalize(&existing);
+    if options.dry_run && !options.diff {
+        return pager::print(&normalized);
+    }
+    if normalized == existing {
+        status!("{} is already tidy", output.display());
+        return Ok(());
+    }
+    if options.diff && !preview_changes(output, &existing, &normalized, options)? {
+        if !options.dry_run {
+            note!("Left {} unchanged", output.display());
+        }
+        return Ok(());
     }
+    replace_file(output, Some(&existing), &normalized, vec!["normalize".into()], options)?;
+    status!("Normalized {}", output.display());
+    Ok(())
 }
 
-fn run() -> Result<(), DynError> {
-    let cli = Cli::parse();
-    let client = Client::builder().user_agent(USER_AGENT).build()?;
-    let ttl = Duration::from_secs(cli.cache_ttl_minutes * 60);
+/// `import-hg`: writes the gitignore form of `file` as an "Imported" section.
+fn import_hgignore(
+    fetcher: &Fetcher,
+    file: &Path,
+    outputs: &[PathBuf],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let content = fs::read_to_string(file)
+        .map_err(|err| format!("Cannot read {}: {err}", file.display()))?;
+    let conversion = hgignore::convert(&content);
+    for (line, rule) in &conversion.untranslated {
+        note!("{}:{line}: no gitignore equivalent for `{rule}`", file.display());
+    }
+    if conversion.patterns.is_empty() {
+        return Err(format!("Nothing to import from {}", file.display()).into());
+    }
+    let imported = Template {
+        name: IMPORTED_SECTION.to_string(),
+        content: conversion.patterns.join("\n") + "\n",
+        provenance: None,
+    };
+    let mut failures = Failures::default();
+    for output in outputs {
+        failures.capture(output.display().to_string(), || {
+            // Importing again refreshes the section rather than skipping it.
+            let existing = read_output(output)?;
+            let mut document = Document::parse(&existing);
+            document.end_open_sections(|section| fetcher.written_locally(section));
+            if output == Path::new(STDOUT) || !document.replace(&imported) {
+                return write_templates(output, options, slice::from_ref(&imported));
+            }
+            let replaced = document.render();
+            if replaced == existing {
+                status!("{} is already up to date", output.display());
+                return Ok(());
+            }
+            if options.dry_run && !options.diff {
+                return pager::print(&replaced);
+            }
+            if options.diff && !preview_changes(output, &existing, &replaced, options)? {
+                if !options.dry_run {
+                    note!("Left {} unchanged", output.display());
+                }
+                return Ok(());
+            }
+            let names = vec![imported.name.clone()];
+            replace_file(output, Some(&existing), &replaced, names, options)?;
+            status!("Replaced {} in {}", imported.name, output.display());
+            Ok(())
+        });
+    }
+    failures.into_result()
+}
 
-    if cli.list {
-        let types = load_types(&client, cli.no_cache, ttl)?;
-        types.iter().for_each(|t| println!("{t}"));
+/// `export-svn`: prints the Subversion properties equivalent to `file`, or sets them on
+/// the working copy around it with `apply`.
+fn export_svn(file: &Path, apply: bool, options: &WriteOptions) -> Result<(), DynError> {
+    let content = fs::read_to_string(file)
+        .map_err(|err| format!("Cannot read {}: {err}", file.display()))?;
+    let properties = svn::Properties::convert(&content);
+    for pattern in &properties.untranslated {
+        note!("{}: no svn equivalent for `{pattern}`", file.display());
+    }
+    if properties.global_ignores.is_empty() && properties.ignores.is_empty() {
+        return Err(format!("Nothing to export from {}", file.display()).into());
+    }
+    if !apply || options.dry_run {
+        return pager::print(&properties.render());
+    }
+    let root = file.parent().filter(|dir| !dir.as_os_str().is_empty());
+    properties.apply(root.unwrap_or(Path::new(".")))?;
+    status!("Set svn ignore properties from {}", file.display());
+    Ok(())
+}
+
+/// `reorder`: sorts the sections in `output` alphabetically with `--order alphabetical`,
+/// otherwise puts those named in `types` first, in that order.
+fn reorder_file(
+    fetcher: &Fetcher,
+    output: &Path,
+    types: &[String],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let existing = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let mut document = Document::parse(&existing);
+    document.end_open_sections(|section| fetcher.written_locally(section));
+    match options.order {
+        SectionOrder::Alphabetical => document.reorder(|section| names::fold(&section.name)),
+        SectionOrder::Input if types.is_empty() => {
+            let hint = "name the sections to put first, or pass --order alphabetical";
+            return Err(format!("Nothing to reorder by ({hint})").into());
+        }
+        SectionOrder::Input => document.reorder(|section| {
+            let rank = types.iter().position(|name| names::eq_folded(name, &section.name));
+            rank.unwrap_or(types.len())
+        }),
+    }
+    let reordered = document.render();
+    if options.dry_run && !options.diff {
+        return pager::print(&reordered);
+    }
+    if reordered == existing {
+        status!("{} is already in order", output.display());
         return Ok(());
     }
+    if options.diff && !preview_changes(output, &existing, &reordered, options)? {
+        if !options.dry_run {
+            note!("Left {} unchanged", output.display());
+        }
+        return Ok(());
+    }
+    replace_file(output, Some(&existing), &reordered, vec!["reorder".into()], options)?;
+    status!("Reordered {}", output.display());
+    Ok(())
+}
 
-    let mut selected = cli.types;
-    if selected.is_empty() {
-        let available = load_types(&client, cli.no_cache, ttl)?;
-        let choice = prompt_for_type(&available)?;
-        selected.push(choice);
+/// `remove`: drops the `types` sections from `output`, or those picked from the file's
+/// sections when none are named.
+fn remove_sections(
+    fetcher: &Fetcher,
+    output: &Path,
+    types: Vec<String>,
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let existing = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let mut document = Document::parse(&existing);
+    document.end_open_sections(|section| fetcher.written_locally(section));
+    let types = if types.is_empty() {
+        let present = section_names(&existing);
+        if present.is_empty() {
+            return Err(format!("{} has no template sections", output.display()).into());
+        }
+        if !is_interactive() {
+            return Err("Name the sections to remove (no terminal for the picker)".into());
+        }
+        let describe = |name: &str| listing::description(&find_section(&existing, name)?);
+        picker::pick_many("Select sections to remove", &present, &[], describe, |name| {
+            find_section(&existing, name)
+        })?
+    } else {
+        types
+    };
+    let removed = document.retain(|section| {
+        !types.iter().any(|name| names::eq_folded(name, &section.name))
+    });
+    for name in types.iter().filter(|name| !removed.iter().any(|r| names::eq_folded(r, name))) {
+        note!("No {name} section in {}", output.display());
+    }
+    if removed.is_empty() {
+        return Ok(());
+    }
+    let remaining = document.render();
+    if options.dry_run && !options.diff {
+        return pager::print(&remaining);
     }
+    if options.diff && !preview_changes(output, &existing, &remaining, options)? {
+        if !options.dry_run {
+            note!("Left {} unchanged", output.display());
+        }
+        return Ok(());
+    }
+    replace_file(output, Some(&existing), &remaining, removed.clone(), options)?;
+    removed.iter().for_each(|name| status!("Removed {name}"));
+    Ok(())
+}
 
-    let normalized: Vec<String> = selected
-        .into_iter()
-        .map(normalize_type)
-        .collect();
+/// `sync`: makes the sections of `output` exactly `types`, freshly fetched and in that
+/// order, leaving hand-written lines where they are.
+fn sync_file(
+    fetcher: &Fetcher,
+    output: &Path,
+    types: &[String],
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    let mut failures = Failures::default();
+    let templates = prepare_templates(fetcher, types, options, &mut failures)?;
+    // Syncing without a template that failed to fetch would drop its section.
+    failures.into_result()?;
 
-    let templates = fetch_templates(&client, &normalized)?;
+    let existed = output.exists();
+    let existing = read_output(output)?;
+    let mut document = Document::parse(&existing);
+    document.end_open_sections(|section| fetcher.written_locally(section));
+    let rank = |name: &str| {
+        let rank = templates.iter().position(|tpl| names::eq_folded(&tpl.name, name));
+        rank.unwrap_or(templates.len())
+    };
+    let keep = |section: &Section| {
+        rank(&section.name) < templates.len() || fetcher.is_local_section(&section.name)
+    };
+    for name in document.retain(keep) {
+        note!("Dropping {name} (not in the manifest)");
+    }
+    for tpl in &templates {
+        if !document.replace(tpl) {
+            document.append(tpl);
+        }
+    }
+    document.reorder(|section| rank(&section.name));
+    let synced = document.render();
 
-    let output_path = cli.output.unwrap_or_else(|| PathBuf::from(".gitignore"));
-    write_templates(&output_path, cli.overwrite, cli.dry_run, &templates)?;
+    if options.dry_run && !options.diff {
+        return pager::print(&synced);
+    }
+    if existed && synced == existing {
+        status!("{} is already in sync", output.display());
+        return Ok(());
+    }
+    if options.diff && !preview_changes(output, &existing, &synced, options)? {
+        if !options.dry_run {
+            note!("Left {} unchanged", output.display());
+        }
+        return Ok(());
+    }
+    let names = templates.iter().map(|tpl| tpl.name.clone()).collect();
+    replace_file(output, existed.then_some(existing.as_str()), &synced, names, options)?;
+    status!("Synced {}", output.display());
     Ok(())
 }
 
-fn load_types(client: &Client, no_cache: bool, ttl: Duration) -> Result<Vec<String>, DynError> {
-    if !no_cache {
-        if let Some(cached) = read_cached_types(ttl)? {
-            return Ok(cached);
+/// Writes `contents` over `output` (whose previous content is `before`, `None` if it didn't
+/// exist): creates parents, backs up, writes atomically and records the change for `undo`.
+/// Every line gets `before`'s prevailing line ending unless `options` names one, so
+/// appending to a Windows file doesn't leave it with mixed endings.
+fn replace_file(
+    output: &Path,
+    before: Option<&str>,
+    contents: &str,
+    names: Vec<String>,
+    options: &WriteOptions,
+) -> Result<(), DynError> {
+    if let Some(parent) = output.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.is_dir()
+    {
+        if !options.parents {
+            let hint = "pass --parents to create it";
+            return Err(format!("Directory {} does not exist ({hint})", parent.display()).into());
         }
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Cannot create {}: {err}", parent.display()))?;
     }
-    let fresh = fetch_types(client)?;
-    write_cached_types(&fresh)?;
-    Ok(fresh)
-}
-
-fn fetch_types(client: &Client) -> Result<Vec<String>, DynError> {
-    let res = client.get(TYPES_URL).send()?;
-    if res.status() != StatusCode::OK {
-        return Err(format!(
-            "Failed to fetch types (status {})",
-            res.status()
-        )
-        .into());
+    if let Some(suffix) = &options.backup
+        && before.is_some_and(|content| !content.is_empty())
+    {
+        let copy = backup::create(output, suffix)?;
+        status!("Backed up {} to {}", output.display(), copy.display());
     }
-    let entries: Vec<RepoEntry> = res.json()?;
-    let mut types = Vec::new();
-    for entry in entries {
-        if entry.name.ends_with(".gitignore") {
-            let clean = entry.name.trim_end_matches(".gitignore").to_string();
-            if !clean.is_empty() {
-                types.push(clean);
-            }
+    let ending = options.line_ending.or_else(|| before.and_then(line_endings::detect));
+    let converted = ending.map(|ending| line_endings::convert(contents, ending));
+    let contents = converted.as_deref().unwrap_or(contents);
+    atomic::write(output, contents.as_bytes())?;
+    // Recorded before the hooks run, so a failing hook still leaves the write undoable.
+    if let Some(dir) = &options.journal {
+        // The write already happened; losing the undo record shouldn't fail the run.
+        if let Err(err) = journal::record(dir, output, before, contents, names.clone()) {
+            note!("Could not record {} for undo: {err}", output.display());
         }
     }
-    types.sort();
-    types.dedup();
-    Ok(types)
+    for command in &options.post_write {
+        run_post_write(command, output, &names)?;
+    }
+    Ok(())
 }
 
-#[derive(Deserialize)]
-struct RepoEntry {
-    name: String,
-    #[serde(rename = "type")]
-    _type: Option<String>,
+/// Runs a `post_write` command through the shell, with the written file in `GI_OUTPUT`
+/// and the applied templates, comma-separated, in `GI_TEMPLATES`.
+fn run_post_write(command: &str, output: &Path, names: &[String]) -> Result<(), DynError> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = std::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = std::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    info!(command, "running post-write command");
+    let status = shell
+        .arg(command)
+        .env("GI_OUTPUT", output)
+        .env("GI_TEMPLATES", names.join(","))
+        .status()
+        .map_err(|err| format!("Cannot run post-write command `{command}`: {err}"))?;
+    if !status.success() {
+        return Err(format!("Post-write command `{command}` failed ({status})").into());
+    }
+    Ok(())
 }
 
-fn prompt_for_type(types: &[String]) -> Result<String, DynError> {
-    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a gitignore template")
-        .items(types)
-        .default(0)
-        .interact()?;
-    Ok(types
-        .get(selection)
-        .cloned()
-        .ok_or("Selection out of range")?)
+/// Prints `Name:line:text` for every template line containing `pattern`,
+/// downloading (and caching) templates that aren't cached yet unless `cached_only`.
+fn grep_templates(
+    fetcher: &Fetcher,
+    types: &[String],
+    pattern: &str,
+    ignore_case: bool,
+    cached_only: bool,
+) -> Result<(), DynError> {
+    let needle = if ignore_case {
+        names::fold(pattern)
+    } else {
+        pattern.to_string()
+    };
+    let mut failures = Failures::default();
+    let mut hits = 0;
+    for name in types {
+        let content = if cached_only {
+            read_cached_template(&fetcher.source, name)
+        } else {
+            failures.capture(name.as_str(), || fetcher.cached_or_download(name))
+        };
+        let Some(content) = content else {
+            continue;
+        };
+        for (idx, line) in content.lines().enumerate() {
+            let matched = if ignore_case {
+                names::fold(line).contains(&needle)
+            } else {
+                line.contains(&needle)
+            };
+            if matched {
+                println!("{name}:{}:{line}", idx + 1);
+                hits += 1;
+            }
+        }
+    }
+    if hits == 0 && failures.is_empty() {
+        return Err(format!("No templates contain '{pattern}'").into());
+    }
+    failures.into_result()
 }
 
-#[derive(Debug)]
-struct Template {
-    name: String,
-    content: String,
+/// Styles comments and negations so they stand out from plain patterns.
+/// `console` drops the styling when stdout colors are off (see `color::init`).
+fn highlight_template(content: &str) -> String {
+    let mut out = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let styled = if trimmed.starts_with('#') {
+            style(line).dim().green().to_string()
+        } else if trimmed.starts_with('!') {
+            style(line).yellow().to_string()
+        } else {
+            line.to_string()
+        };
+        out.push_str(&styled);
+        out.push('\n');
+    }
+    out
 }
 
-fn fetch_templates(client: &Client, types: &[String]) -> Result<Vec<Template>, DynError> {
-    let mut out = Vec::new();
-    for t in types {
-        if let Some(snippet) = built_in_flag(t) {
-            out.push(Template {
-                name: t.clone(),
-                content: snippet,
-            });
+fn diff_sections(fetcher: &Fetcher, output: &Path, types: &[String]) -> Result<(), DynError> {
+    let local = fs::read_to_string(output)
+        .map_err(|err| format!("Cannot read {}: {err}", output.display()))?;
+    let document = Document::parse(&local);
+    let mut failures = Failures::default();
+    let mut diffs = String::new();
+    for (name, found) in expected_sections(fetcher, &document, types.to_vec(), &mut failures) {
+        let Some((section, upstream)) = found else {
+            failures.push(name, format!("No section in {}", output.display()).into());
             continue;
+        };
+        let local_label = format!("{} ({name})", output.display());
+        let upstream_label = format!("upstream/{name}.gitignore");
+        match unified_diff(&section.body(), &upstream, &local_label, &upstream_label) {
+            Some(diff) => diffs.push_str(&diff),
+            None => status!("{name} is up to date"),
         }
-        let url = format!("{RAW_BASE_URL}{t}.gitignore");
-        let res = client.get(&url).send()?;
-        if res.status() != StatusCode::OK {
-            return Err(format!(
-                "Template '{}' not found (status {})",
-                t,
-                res.status()
-            )
-            .into());
-        }
-        let content = res.text()?;
-        out.push(Template {
-            name: t.clone(),
-            content,
-        });
     }
-    Ok(out)
+    pager::print(&diffs)?;
+    failures.into_result()
 }
 
-fn write_templates(
-    output: &Path,
-    overwrite: bool,
-    dry_run: bool,
-    templates: &[Template],
-) -> Result<(), DynError> {
-    if dry_run {
-        for tpl in templates {
-            println!("# --- {} ---", tpl.name);
-            print!("{}", tpl.content);
-            if !tpl.content.ends_with('\n') {
-                println!();
+/// Checks the given sections (or every section in the file) against upstream.
+fn check_sections(fetcher: &Fetcher, output: &Path, types: &[String]) -> Result<(), DynError> {
+    let local = read_output(output)?;
+    let names = if types.is_empty() {
+        section_names(&local)
+    } else {
+        types.to_vec()
+    };
+    if names.is_empty() {
+        return Err(format!("No template sections in {}", output.display()).into());
+    }
+
+    let document = Document::parse(&local);
+    let mut failures = Failures::default();
+    for (name, found) in expected_sections(fetcher, &document, names, &mut failures) {
+        match found {
+            None => failures.push(name, format!("missing from {}", output.display()).into()),
+            Some((section, upstream)) if section.body() != upstream => {
+                failures.push(name, "out of date with upstream (see `gi diff`)".into())
             }
-            println!();
+            Some(_) => status!("{name} is up to date"),
         }
-        return Ok(());
     }
+    failures.into_result()
+}
 
-    if overwrite {
-        let mut buffer = String::new();
-        for tpl in templates {
-            buffer.push_str(&format!("# --- {} ---\n", tpl.name));
-            buffer.push_str(&tpl.content);
-            if !tpl.content.ends_with('\n') {
-                buffer.push('\n');
+/// The sections of `document` for `names`, each with what its body would be if written
+/// from upstream today, rewritten the way it was (see `tidy::Transforms`). A section
+/// merged with `--dedupe-lines` stands for each of its templates and is listed once;
+/// names without a section come with `None`. Fetch failures go to `failures`.
+fn expected_sections<'a>(
+    fetcher: &Fetcher,
+    document: &'a Document,
+    names: Vec<String>,
+    failures: &mut Failures,
+) -> Vec<(String, Option<(&'a Section, String)>)> {
+    let parts = |section: &'a Section| -> Vec<&'a str> {
+        if Transforms::parse(section.written_with()).dedupe_lines {
+            tidy::merged_parts(&section.name)
+        } else {
+            vec![section.name.as_str()]
+        }
+    };
+    let containing = |name: &str| {
+        document.section(name).or_else(|| {
+            document
+                .sections()
+                .find(|section| parts(section).iter().any(|part| names::eq_folded(part, name)))
+        })
+    };
+    let mut wanted: Vec<String> = Vec::new();
+    for name in upstream_sections(fetcher, names) {
+        let found = containing(&name).map(parts).unwrap_or_else(|| vec![name.as_str()]);
+        for part in found {
+            if !wanted.iter().any(|known| names::eq_folded(known, part)) {
+                wanted.push(part.to_string());
             }
-            buffer.push('\n');
         }
-        fs::write(output, buffer)?;
-        println!("Wrote templates to {}", output.display());
-        return Ok(());
     }
-
-    let existing_content = fs::read_to_string(output).unwrap_or_default();
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(output)?;
-    for tpl in templates {
-        if !existing_content.is_empty() && existing_content.contains(&tpl.content) {
-            eprintln!("Skipping {} (already present)", tpl.name);
+    let templates = fetcher.fetch_all(&wanted, failures);
+    let mut expected: Vec<(String, Option<(&Section, String)>)> = Vec::new();
+    for tpl in &templates {
+        let Some(section) = containing(&tpl.name) else {
+            expected.push((tpl.name.clone(), None));
+            continue;
+        };
+        if expected.iter().any(|(name, _)| *name == section.name) {
             continue;
         }
-        if needs_separator(&file)? {
-            file.write_all(b"\n")?;
-        }
-        file.write_all(format!("# --- {} ---\n", tpl.name).as_bytes())?;
-        file.write_all(tpl.content.as_bytes())?;
-        if !tpl.content.ends_with('\n') {
-            file.write_all(b"\n")?;
+        let sources: Option<Vec<&Template>> = parts(section)
+            .iter()
+            .map(|part| templates.iter().find(|tpl| names::eq_folded(&tpl.name, part)))
+            .collect();
+        // A part that failed to fetch is already in `failures`.
+        let Some(sources) = sources else { continue };
+        let transforms = Transforms::parse(section.written_with());
+        let content = if transforms.dedupe_lines {
+            tidy::merge(sources.iter().map(|tpl| (tpl.name.as_str(), tpl.content.as_str())))
+        } else {
+            sources[0].content.clone()
+        };
+        let body = section_body(&transforms.apply(&content));
+        expected.push((section.name.clone(), Some((section, body))));
+    }
+    expected
+}
+
+/// `names` without the sections written from local patterns, which have no upstream to
+/// compare with.
+fn upstream_sections(fetcher: &Fetcher, names: Vec<String>) -> Vec<String> {
+    let (local, upstream): (Vec<String>, Vec<String>) =
+        names.into_iter().partition(|name| fetcher.is_local_section(name));
+    for name in local {
+        status!("{name} is written locally; skipping");
+    }
+    upstream
+}
+
+/// The output's current content, where a missing file reads as empty. Any other reason it
+/// can't be read, such as bytes that aren't UTF-8, is an error: treating the file as empty
+/// would overwrite what is in it.
+fn read_output(output: &Path) -> Result<String, DynError> {
+    match fs::read_to_string(output) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            Err(format!("{} is not UTF-8 text; leaving it alone", output.display()).into())
         }
-        file.write_all(b"\n")?;
-        file.flush()?;
-        println!("Appended {}", tpl.name);
+        Err(err) => Err(format!("Cannot read {}: {err}", output.display()).into()),
     }
-    Ok(())
 }
 
-fn needs_separator(file: &File) -> Result<bool, io::Error> {
-    let meta = file.metadata()?;
-    Ok(meta.len() > 0)
+fn section_header(name: &str) -> String {
+    format!("# --- {name} ---")
+}
+
+fn section_names(content: &str) -> Vec<String> {
+    Document::parse(content).sections().map(|section| section.name.clone()).collect()
 }
 
-fn normalize_type(input: String) -> String {
-    if input.starts_with("--") {
-        return input;
+/// Returns the body of the `name` section, which runs until the next header.
+fn find_section(content: &str, name: &str) -> Option<String> {
+    Document::parse(content).section(name).map(Section::body)
+}
+
+/// Normalizes section text so trailing blank separators don't count as changes.
+fn section_body(content: &str) -> String {
+    let trimmed = content.trim_end();
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{trimmed}\n")
+    }
+}
+
+/// `--github-host`, or `--api-url`/`--raw-url`; the config's keys of the same names when
+/// none are given.
+fn source_hosts(cli: &Cli, config: &Config) -> Hosts {
+    let given = [&cli.github_host, &cli.api_url, &cli.raw_url];
+    if given.iter().any(|value| value.is_some()) {
+        hosts_for(&cli.github_host, &cli.api_url, &cli.raw_url)
+    } else {
+        hosts_for(&config.github_host, &config.api_url, &config.raw_url)
+    }
+}
+
+/// The hosts a `github_host` (`github.com` being the default) and explicit base URLs make.
+fn hosts_for(host: &Option<String>, api: &Option<String>, raw: &Option<String>) -> Hosts {
+    let host = host.as_deref().filter(|host| host.trim_end_matches('/') != "github.com");
+    let mut hosts = host.map_or_else(Hosts::default, Hosts::enterprise);
+    if let Some(api) = api {
+        hosts.api = api.trim_end_matches('/').to_string();
     }
-    let mut chars = input.chars();
-    match chars.next() {
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-        None => input,
+    if let Some(raw) = raw {
+        hosts.raw = raw.trim_end_matches('/').to_string();
     }
+    hosts
 }
 
-fn built_in_flag(flag: &str) -> Option<String> {
-    match flag {
-        "--macos" => Some("# Desktop Service Store Mac\n.DS_Store\n".to_string()),
-        "--locks" => Some("# Lock Files\npackage-lock.json\nyarn.lock\n".to_string()),
-        _ => None,
+/// The template piped in for `-` / `--stdin`, which must not be empty.
+fn read_piped_template() -> Result<String, DynError> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return Err("Nothing piped in for the stdin template (`-`)".into());
     }
+    let mut content = String::new();
+    stdin.lock().read_to_string(&mut content)?;
+    if content.trim().is_empty() {
+        return Err("The template piped to stdin is empty".into());
+    }
+    Ok(content)
+}
+
+/// Maps aliases (`osx`, `golang`, ...) to upstream names; casing is fixed when fetching
+/// (see `Fetcher::canonical_name`).
+fn resolve_type(input: String, aliases: &BTreeMap<String, String>) -> String {
+    aliases::resolve(&input, aliases).unwrap_or(input)
 }
 
-fn read_cached_types(ttl: Duration) -> Result<Option<Vec<String>>, DynError> {
+fn read_cached_types(
+    source: &Source,
+    ttl: Duration,
+) -> Result<Option<Vec<TemplateInfo>>, DynError> {
     let path = cache_file_path()?;
     if !path.exists() {
         return Ok(None);
     }
+    let _lock = cache::lock_shared(&cache_dir()?)?;
     let contents = fs::read_to_string(path)?;
-    let cached: CachedTypes = serde_json::from_str(&contents)?;
-    if cached.is_fresh(ttl) {
-        Ok(Some(cached.types))
+    let Ok(cached) = serde_json::from_str::<CachedTypes>(&contents) else {
+        // Left by a crash under an older version; refetching overwrites it.
+        info!("ignoring unreadable cached template list");
+        return Ok(None);
+    };
+    // Caches written before metadata existed only hold names; refetch those.
+    let usable = cached.source == source.to_string() && !cached.entries.is_empty();
+    if usable && cached.is_fresh(ttl) {
+        Ok(Some(cached.entries))
     } else {
         Ok(None)
     }
 }
 
-fn write_cached_types(types: &[String]) -> Result<(), DynError> {
+fn write_cached_types(source: &Source, catalog: &[TemplateInfo]) -> Result<(), DynError> {
     let path = cache_file_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
+    let _lock = cache::lock_exclusive(&cache_dir()?)?;
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)?
         .as_secs();
     let cached = CachedTypes {
         fetched_at: now,
-        types: types.to_vec(),
+        source: source.to_string(),
+        types: catalog.iter().map(|info| info.name.clone()).collect(),
+        entries: catalog.to_vec(),
     };
     let serialized = serde_json::to_string(&cached)?;
-    fs::write(path, serialized)?;
+    atomic::write(&path, serialized.as_bytes())?;
+    Ok(())
+}
+
+fn read_cached_template(source: &Source, name: &str) -> Option<String> {
+    let path = template_cache_path(source, name).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    let _lock = cache::lock_shared(&cache_dir().ok()?).ok()?;
+    let content = fs::read_to_string(&path).ok()?;
+    let _ = cache::touch(&path);
+    Some(content)
+}
+
+fn write_cached_template(
+    source: &Source,
+    name: &str,
+    content: &str,
+    max_size: Option<u64>,
+) -> Result<(), DynError> {
+    write_cache_file(&template_cache_path(source, name)?, content, max_size)
+}
+
+fn read_cached_blob(sha: &str) -> Option<String> {
+    let path = blob_cache_path(sha).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    let _lock = cache::lock_shared(&cache_dir().ok()?).ok()?;
+    let content = fs::read_to_string(&path).ok()?;
+    // Guards against a truncated or hand-edited file under the right name.
+    if verify::git_blob_sha(content.as_bytes()) != sha {
+        return None;
+    }
+    let _ = cache::touch(&path);
+    Some(content)
+}
+
+/// Stores `content` under its own blob SHA, so it is never downloaded twice.
+fn write_cached_blob(content: &str, max_size: Option<u64>) -> Result<(), DynError> {
+    let sha = verify::git_blob_sha(content.as_bytes());
+    write_cache_file(&blob_cache_path(&sha)?, content, max_size)
+}
+
+fn write_cache_file(path: &Path, content: &str, max_size: Option<u64>) -> Result<(), DynError> {
+    let dir = cache_dir()?;
+    let _lock = cache::lock_exclusive(&dir)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    atomic::write(path, content.as_bytes())?;
+    if let Some(max_size) = max_size {
+        let keep = path.strip_prefix(&dir).unwrap_or(path);
+        for entry in cache::evict(&dir, max_size, keep)? {
+            debug!(path = %entry.path.display(), "evicted from cache");
+        }
+    }
+    Ok(())
+}
+
+/// Whether upstream answered 404 for `name` within `MISSING_TTL`.
+fn recently_missing(source: &Source, name: &str) -> bool {
+    let Ok(path) = missing_cache_path(source, name) else {
+        return false;
+    };
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|at| at.elapsed().is_ok_and(|age| age < MISSING_TTL))
+}
+
+fn record_missing(source: &Source, name: &str) -> Result<(), DynError> {
+    let path = missing_cache_path(source, name)?;
+    let _lock = cache::lock_exclusive(&cache_dir()?)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    atomic::write(&path, b"")
+}
+
+fn manage_cache(action: CacheAction) -> Result<(), DynError> {
+    let dir = cache_dir()?;
+    match action {
+        CacheAction::Path => println!("{}", dir.display()),
+        CacheAction::Info => {
+            let entries = {
+                let _lock = cache::lock_shared(&dir)?;
+                cache::entries(&dir)?
+            };
+            if entries.is_empty() {
+                status!("Cache at {} is empty", dir.display());
+                return Ok(());
+            }
+            for entry in &entries {
+                println!(
+                    "{}\t{}\t{} old",
+                    entry.path.display(),
+                    cache::format_size(entry.size),
+                    cache::format_age(entry.age())
+                );
+            }
+            let total = entries.iter().map(|entry| entry.size).sum();
+            status!(
+                "{} file(s), {} in {}",
+                entries.len(),
+                cache::format_size(total),
+                dir.display()
+            );
+        }
+        CacheAction::Clear { types: true } => {
+            let path = cache_file_path()?;
+            let _lock = cache::lock_exclusive(&dir)?;
+            match fs::remove_file(&path) {
+                Ok(()) => status!("Removed {}", path.display()),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    status!("No cached template list");
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        CacheAction::Clear { types: false } => {
+            // Everything but the lock file, which other runs may be waiting on.
+            let _lock = cache::lock_exclusive(&dir)?;
+            let removed = cache::purge(&dir, Duration::ZERO)?;
+            status!("Cleared {} ({} file(s))", dir.display(), removed.len());
+        }
+        CacheAction::Purge { older_than } => {
+            let _lock = cache::lock_exclusive(&dir)?;
+            let purged = cache::purge(&dir, older_than)?;
+            for entry in &purged {
+                debug!(path = %entry.path.display(), "purged");
+            }
+            let age = cache::format_age(older_than);
+            status!("Purged {} file(s) older than {age}", purged.len());
+        }
+        CacheAction::Export { bundle } => {
+            let _lock = cache::lock_shared(&dir)?;
+            let count = cache::export(&dir, &bundle)
+                .map_err(|err| format!("Cannot write {}: {err}", bundle.display()))?;
+            status!("Exported {count} file(s) to {}", bundle.display());
+        }
+        CacheAction::Import { bundle } => {
+            let _lock = cache::lock_exclusive(&dir)?;
+            let count = cache::import(&dir, &bundle)
+                .map_err(|err| format!("Cannot import {}: {err}", bundle.display()))?;
+            status!("Imported {count} file(s) into {}", dir.display());
+        }
+    }
     Ok(())
 }
 
 fn cache_file_path() -> Result<PathBuf, DynError> {
+    Ok(cache_dir()?.join(CACHE_FILE))
+}
+
+fn template_cache_path(source: &Source, name: &str) -> Result<PathBuf, DynError> {
+    let dir = source_cache_dir(&cache_dir()?.join(TEMPLATE_CACHE_DIR), source);
+    Ok(dir.join(format!("{name}.gitignore")))
+}
+
+/// `owner/repo/ref` under `base`, inside a per-host directory for GitHub Enterprise.
+fn source_cache_dir(base: &Path, source: &Source) -> PathBuf {
+    let base = if source.hosts.is_github() {
+        base.to_path_buf()
+    } else {
+        base.join(source.hosts.cache_dir_name())
+    };
+    base.join(&source.owner).join(&source.repo).join(&source.reference)
+}
+
+/// `blobs/ab/cdef...`, fanned out like `.git/objects` to keep directories small.
+fn blob_cache_path(sha: &str) -> Result<PathBuf, DynError> {
+    if sha.len() < 3 || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid blob SHA `{sha}`").into());
+    }
+    Ok(cache_dir()?.join(BLOB_CACHE_DIR).join(&sha[..2]).join(&sha[2..]))
+}
+
+fn missing_cache_path(source: &Source, name: &str) -> Result<PathBuf, DynError> {
+    Ok(source_cache_dir(&cache_dir()?.join(MISSING_CACHE_DIR), source).join(name))
+}
+
+fn cache_dir() -> Result<PathBuf, DynError> {
     let proj = ProjectDirs::from("dev", "gitignore-downloader", "gitignore-downloader")
         .ok_or("Cannot determine cache directory")?;
-    Ok(proj.cache_dir().join(CACHE_FILE))
+    Ok(proj.cache_dir().to_path_buf())
 }
 
 #[cfg(test)]
@@ -313,17 +3750,12 @@ mod tests {
     use std::time::{Duration, SystemTime};
 
     #[test]
-    fn normalizes_simple_type() {
-        assert_eq!(normalize_type("rust".into()), "Rust");
-        assert_eq!(normalize_type("Rust".into()), "Rust");
-    }
-
-    #[test]
-    fn preserves_flags() {
-        assert_eq!(normalize_type("--macos".into()), "--macos");
-        assert!(built_in_flag("--macos").is_some());
-        assert!(built_in_flag("--locks").is_some());
-        assert!(built_in_flag("--nope").is_none());
+    fn escapes_table_fields() {
+        assert_eq!(ListFormat::Tsv.row(&["a\tb", "c"]), "a b\tc");
+        assert_eq!(
+            ListFormat::Csv.row(&["C++", "a,b", "say \"hi\""]),
+            r#"C++,"a,b","say ""hi""""#
+        );
     }
 
     #[test]
@@ -333,13 +3765,17 @@ mod tests {
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            source: String::new(),
             types: vec![],
+            entries: vec![],
         };
         assert!(cached.is_fresh(Duration::from_secs(10)));
 
         let stale = CachedTypes {
             fetched_at: 0,
+            source: String::new(),
             types: vec![],
+            entries: vec![],
         };
         assert!(!stale.is_fresh(Duration::from_secs(1)));
     }
@@ -353,21 +3789,29 @@ mod tests {
             Template {
                 name: "Rust".to_string(),
                 content: "target/\n".to_string(),
+                provenance: None,
             },
             Template {
                 name: "Node".to_string(),
                 content: "node_modules/\n".to_string(),
+                provenance: None,
             },
         ];
 
-        write_templates(&path, true, false, &templates).unwrap();
+        let overwrite = WriteOptions {
+            overwrite: true,
+            ..WriteOptions::default()
+        };
+        write_templates(&path, &overwrite, &templates).unwrap();
 
         let written = fs::read_to_string(&path).unwrap();
         let expected = "\
 # --- Rust ---\n\
-target/\n\n\
+target/\n\
+# --- end Rust ---\n\n\
 # --- Node ---\n\
-node_modules/\n\n";
+node_modules/\n\
+# --- end Node ---\n";
         assert_eq!(written, expected);
 
         let _ = fs::remove_file(&path);
@@ -382,22 +3826,86 @@ node_modules/\n\n";
             Template {
                 name: "Rust".to_string(),
                 content: "target/\n".to_string(),
+                provenance: None,
             },
             Template {
                 name: "Node".to_string(),
                 content: "node_modules/\n".to_string(),
+                provenance: None,
             },
         ];
 
-        write_templates(&path, false, false, &templates).unwrap();
+        write_templates(&path, &WriteOptions::default(), &templates).unwrap();
 
         let written = fs::read_to_string(&path).unwrap();
-        let expected = "Existing\ntarget/\n\n# --- Node ---\nnode_modules/\n\n";
+        let expected = "Existing\ntarget/\n\n# --- Node ---\nnode_modules/\n# --- end Node ---\n";
         assert_eq!(written, expected);
 
         let _ = fs::remove_file(&path);
     }
 
+    #[test]
+    fn finds_section_until_next_header() {
+        let content = "\
+# --- Rust ---\n\
+target/\n\
+\n\
+# --- Node ---\n\
+node_modules/\n\n";
+        assert_eq!(find_section(content, "Rust").as_deref(), Some("target/\n"));
+        assert_eq!(find_section(content, "Node").as_deref(), Some("node_modules/\n"));
+        assert!(find_section(content, "Go").is_none());
+        assert_eq!(section_names(content), vec!["Rust", "Node"]);
+    }
+
+    #[test]
+    fn check_sections_reports_stale_and_missing_sections() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let rust = fs::read_to_string(fixtures.join("raw/Rust.gitignore")).unwrap();
+        let path = temp_path("check-sections");
+        fs::write(&path, format!("# --- Rust ---\n{rust}\n# --- Node ---\nstale/\n")).unwrap();
+        let fetcher = fixture_fetcher(fixtures);
+
+        assert!(check_sections(&fetcher, &path, &["Rust".to_string()]).is_ok());
+        let names = ["Rust", "Node", "Global/macOS"].map(String::from);
+        let err = check_sections(&fetcher, &path, &names).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "2 operations failed:\n  Node: out of date with upstream (see `gi diff`)\n  \
+                 Global/macOS: missing from {}",
+                path.display()
+            )
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    /// A fetcher answering from `fixtures` only, with no config.
+    fn fixture_fetcher(fixtures: PathBuf) -> Fetcher {
+        Fetcher {
+            client: Client::new(),
+            source: Source::default(),
+            manifest: None,
+            rules: BTreeMap::new(),
+            snippets: Snippets::load(&BTreeMap::new(), &temp_path("no-snippets")).unwrap(),
+            fixtures: Some(fixtures),
+            cache_max_size: None,
+            no_cache: false,
+            locked: false,
+            fallbacks: Vec::new(),
+            bundle: None,
+            bundled: OnceCell::new(),
+            overrides: temp_path("no-overrides"),
+            origins: RefCell::default(),
+            commits: RefCell::default(),
+            pins: BTreeMap::new(),
+            piped: None,
+            catalog_names: OnceCell::new(),
+            blob_shas: RefCell::default(),
+        }
+    }
+
     fn temp_path(name: &str) -> PathBuf {
         let unique = SystemTime::now()
             .duration_since(UNIX_EPOCH)
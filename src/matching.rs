@@ -0,0 +1,126 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{self, Path, PathBuf};
+
+use crate::DynError;
+
+/// Which rule, if any, decides a path, with its 1-based line number.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Ignored { rule: String, line: usize },
+    /// A `!` rule re-includes the path.
+    Included { rule: String, line: usize },
+    NotMatched,
+    /// The path isn't under the directory the file applies to.
+    Outside,
+}
+
+/// A `.gitignore` body evaluated with git's semantics, relative to `root`.
+pub struct Matcher {
+    root: PathBuf,
+    gitignore: Gitignore,
+    lines: Vec<String>,
+}
+
+impl Matcher {
+    pub fn new(root: &Path, content: &str) -> Result<Self, DynError> {
+        let root = path::absolute(root)?;
+        let mut builder = GitignoreBuilder::new(&root);
+        for line in content.lines() {
+            builder.add_line(None, line)?;
+        }
+        Ok(Matcher {
+            gitignore: builder.build()?,
+            lines: content.lines().map(str::to_string).collect(),
+            root,
+        })
+    }
+
+    /// Decides `path` (relative to the current directory) the way git would, including
+    /// rules on its parent directories.
+    pub fn check(&self, path: &Path, is_dir: bool) -> Result<Verdict, DynError> {
+        // Rebuilding from components drops a trailing `/`, which would defeat matching.
+        let path: PathBuf = path::absolute(path)?.components().collect();
+        if !path.starts_with(&self.root) || path == self.root {
+            return Ok(Verdict::Outside);
+        }
+        // git walks down from the root and never looks inside an excluded directory, so the
+        // outermost excluded parent decides, whatever deeper rules say.
+        let mut parents: Vec<&Path> = path.ancestors().skip(1).collect();
+        parents.retain(|dir| dir.starts_with(&self.root) && *dir != self.root);
+        let excluded_parent = parents
+            .iter()
+            .rev()
+            .map(|dir| self.gitignore.matched(dir, true))
+            .find(Match::is_ignore);
+        let matched = excluded_parent.unwrap_or_else(|| self.gitignore.matched(&path, is_dir));
+        let (glob, ignored) = match matched {
+            Match::None => return Ok(Verdict::NotMatched),
+            Match::Ignore(glob) => (glob, true),
+            Match::Whitelist(glob) => (glob, false),
+        };
+        let rule = glob.original().to_string();
+        // Later rules win, so the deciding line is the last one with this text.
+        let line = self.lines.iter().rposition(|l| l.trim_end() == rule).map_or(0, |i| i + 1);
+        Ok(if ignored {
+            Verdict::Ignored { rule, line }
+        } else {
+            Verdict::Included { rule, line }
+        })
+    }
+}
+
+/// A one-off pattern for an untracked path as `git status` lists it: the directory for
+/// `dir/`, `*.ext` for files with an extension, otherwise the file anchored at the root.
+pub fn propose_pattern(path: &str) -> String {
+    if let Some(dir) = path.strip_suffix('/') {
+        return if dir.contains('/') { format!("/{path}") } else { path.to_string() };
+    }
+    match Path::new(path).extension() {
+        Some(ext) => format!("*.{}", ext.to_string_lossy()),
+        None => format!("/{path}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proposes_patterns_for_leftovers() {
+        assert_eq!(propose_pattern("coverage/"), "coverage/");
+        assert_eq!(propose_pattern("docs/build/"), "/docs/build/");
+        assert_eq!(propose_pattern("app.iml"), "*.iml");
+        assert_eq!(propose_pattern("scratch"), "/scratch");
+    }
+
+    #[test]
+    fn reports_the_deciding_rule() {
+        let root = std::env::temp_dir();
+        let matcher = Matcher::new(&root, "# Logs\n*.log\n!keep.log\ntarget/\n").unwrap();
+        let check = |path: &str, is_dir| matcher.check(&root.join(path), is_dir).unwrap();
+
+        assert_eq!(
+            check("debug.log", false),
+            Verdict::Ignored { rule: "*.log".into(), line: 2 }
+        );
+        assert_eq!(
+            check("keep.log", false),
+            Verdict::Included { rule: "!keep.log".into(), line: 3 }
+        );
+        assert_eq!(
+            check("target/debug/gi", false),
+            Verdict::Ignored { rule: "target/".into(), line: 4 }
+        );
+        assert_eq!(
+            check("target/debug/keep.log", false),
+            Verdict::Ignored { rule: "target/".into(), line: 4 }
+        );
+        assert_eq!(
+            check("target/", true),
+            Verdict::Ignored { rule: "target/".into(), line: 4 }
+        );
+        assert_eq!(check("src/main.rs", false), Verdict::NotMatched);
+        assert_eq!(matcher.check(Path::new("/"), true).unwrap(), Verdict::Outside);
+    }
+}
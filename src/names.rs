@@ -0,0 +1,183 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+/// Case-folds a template name the same way on every platform and locale,
+/// so sorting and matching never depend on the user's environment.
+pub fn fold(name: &str) -> String {
+    name.chars().flat_map(char::to_lowercase).collect()
+}
+
+pub fn eq_folded(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// How `input` is spelled in `known`: an exact match, else a case-insensitive one, else
+/// the only name that matches once case and word separators are ignored
+/// (`visual-studio` and `visualstudio` → `VisualStudio`).
+pub fn canonical<'a>(input: &str, known: &'a [String]) -> Option<&'a str> {
+    if let Some(exact) = known.iter().find(|name| *name == input) {
+        return Some(exact);
+    }
+    if let Some(folded) = known.iter().find(|name| eq_folded(name, input)) {
+        return Some(folded);
+    }
+    let compact = compact(input);
+    let mut loose = known.iter().filter(|name| self::compact(name) == compact);
+    match (loose.next(), loose.next()) {
+        (Some(only), None) => Some(only),
+        _ => None,
+    }
+}
+
+/// Known names `input` could be short for: those it starts (`jet` → `Global/JetBrains`,
+/// looking past the directory too), or failing that those it fuzzily matches, best first.
+pub fn partial_matches<'a>(input: &str, known: &'a [String]) -> Vec<&'a str> {
+    let folded = fold(input);
+    let prefixed: Vec<&str> = known
+        .iter()
+        .filter(|name| {
+            let base = name.rsplit('/').next().unwrap_or(name);
+            fold(name).starts_with(&folded) || fold(base).starts_with(&folded)
+        })
+        .map(String::as_str)
+        .collect();
+    if !prefixed.is_empty() {
+        return prefixed;
+    }
+    fuzzy_rank(known, input).into_iter().map(|index| known[index].as_str()).collect()
+}
+
+/// A folded name without spaces, `-` or `_`, for matching multi-word names however
+/// they were typed.
+fn compact(name: &str) -> String {
+    fold(name).replace([' ', '-', '_'], "")
+}
+
+/// `name` with its first letter upper-cased, the usual upstream spelling (`rust` → `Rust`),
+/// for when there is no template list to look the name up in.
+pub fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Orders by folded name, falling back to code points so the result is total.
+pub fn sort_and_dedup<T>(items: &mut Vec<T>, name: impl Fn(&T) -> &str) {
+    items.sort_by_cached_key(|item| (fold(name(item)), name(item).to_string()));
+    items.dedup_by(|a, b| name(a) == name(b));
+}
+
+/// Indices of `items` matching `query`, best first: exact (folded) matches,
+/// then substring matches, then fuzzy matches by score.
+pub fn fuzzy_rank(items: &[String], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..items.len()).collect();
+    }
+    let matcher = SkimMatcherV2::default();
+    let folded_query = fold(query);
+    let mut scored: Vec<(u8, i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| {
+            let score = matcher.fuzzy_match(item, query)?;
+            let folded = fold(item);
+            let tier = if folded == folded_query {
+                0
+            } else if folded.contains(&folded_query) {
+                1
+            } else {
+                2
+            };
+            Some((tier, -score, idx))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, _, idx)| idx).collect()
+}
+
+/// Up to three known names within a small edit distance of `input`, closest first.
+pub fn closest(input: &str, known: &[String]) -> Vec<String> {
+    let folded = fold(input);
+    let threshold = (folded.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &String)> = known
+        .iter()
+        .map(|name| (strsim::levenshtein(&folded, &fold(name)), name))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    scored.sort();
+    scored.into_iter().take(3).map(|(_, name)| name.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_case_insensitively_and_dedups() {
+        let mut names: Vec<String> = ["Rust", "iOS", "Ada", "Ärger", "Go", "ada", "Rust"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        sort_and_dedup(&mut names, |name| name);
+        assert_eq!(names, vec!["Ada", "ada", "Go", "iOS", "Rust", "Ärger"]);
+
+        assert!(eq_folded("ÄRGER", "ärger"));
+        assert!(!eq_folded("Rust", "Rusty"));
+    }
+
+    #[test]
+    fn finds_the_upstream_spelling() {
+        let known: Vec<String> = ["VisualStudio", "UnrealEngine", "Objective-C", "CMake", "iOS"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(canonical("cmake", &known), Some("CMake"));
+        assert_eq!(canonical("IOS", &known), Some("iOS"));
+        assert_eq!(canonical("visualstudio", &known), Some("VisualStudio"));
+        assert_eq!(canonical("unreal-engine", &known), Some("UnrealEngine"));
+        assert_eq!(canonical("Unreal Engine", &known), Some("UnrealEngine"));
+        assert_eq!(canonical("objectivec", &known), Some("Objective-C"));
+        assert_eq!(canonical("visual", &known), None);
+        assert_eq!(capitalize("rust"), "Rust");
+        assert_eq!(capitalize("CMake"), "CMake");
+    }
+
+    #[test]
+    fn finds_partial_matches() {
+        let known: Vec<String> = ["Global/JetBrains", "Java", "JENKINS_HOME", "Node", "Nim"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(partial_matches("jet", &known), ["Global/JetBrains"]);
+        assert_eq!(partial_matches("j", &known), ["Global/JetBrains", "Java", "JENKINS_HOME"]);
+        assert_eq!(partial_matches("nde", &known), ["Node"]);
+        assert!(partial_matches("xyz", &known).is_empty());
+    }
+
+    #[test]
+    fn suggests_close_names() {
+        let known: Vec<String> = ["Python", "Node", "Go", "Rust"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(closest("Pyhton", &known), vec!["Python"]);
+        assert_eq!(closest("nod", &known), vec!["Node", "Go"]);
+        assert!(closest("Haskell", &known).is_empty());
+    }
+
+    #[test]
+    fn ranks_exact_then_substring_then_fuzzy() {
+        let items: Vec<String> = ["Node", "Rust", "Global/JetBrains", "Ruby", "Rusty"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(fuzzy_rank(&items, ""), vec![0, 1, 2, 3, 4]);
+        assert_eq!(fuzzy_rank(&items, "jet"), vec![2]);
+        assert_eq!(fuzzy_rank(&items, "rust"), vec![1, 4]);
+        assert_eq!(fuzzy_rank(&items, "rb")[0], 3);
+    }
+}
@@ -0,0 +1,57 @@
+use console::Term;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::DynError;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns automatic paging off for the rest of the process (`--no-pager`).
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Prints `text` to stdout, through the pager when stdout is a terminal too short to
+/// show it all, as git does. `--no-pager` always prints directly.
+pub fn print(text: &str) -> Result<(), DynError> {
+    let stdout = io::stdout();
+    let fits = || text.lines().count() < usize::from(Term::stdout().size().0);
+    if DISABLED.load(Ordering::Relaxed) || !stdout.is_terminal() || fits() {
+        print!("{text}");
+        return Ok(());
+    }
+    page(text)
+}
+
+/// Pipes `text` through `$PAGER` (falling back to `less -R`), like git does.
+pub fn page(text: &str) -> Result<(), DynError> {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string());
+    let mut child = shell_command(&pager)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Cannot start pager '{pager}': {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may exit before reading everything (e.g. `q` in less).
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Runs `command` through `sh -c` (`cmd /C` on Windows).
+pub fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
@@ -0,0 +1,124 @@
+use console::{style, Key, Term};
+
+use crate::names;
+use crate::DynError;
+
+const VISIBLE_ROWS: usize = 10;
+const PREVIEW_LINES: usize = 20;
+
+/// Fuzzy multi-select with a preview of the highlighted entry underneath. Drawn by hand
+/// because dialoguer's `MultiSelect` has no search and `FuzzySelect` picks only one.
+///
+/// Type to filter, arrows to move, Tab/Space to toggle, Enter to confirm
+/// (the highlighted entry is used if nothing was toggled), Esc to cancel.
+/// Items in `preselected` start out toggled; `describe` gives the dim text shown beside
+/// an item, if any.
+pub fn pick_many(
+    prompt: &str,
+    items: &[String],
+    preselected: &[String],
+    describe: impl Fn(&str) -> Option<String>,
+    preview: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<String>, DynError> {
+    let term = Term::stderr();
+    let width = usize::from(term.size().1);
+    let descriptions: Vec<Option<String>> = items.iter().map(|item| describe(item)).collect();
+    let mut query = String::new();
+    let mut chosen: Vec<bool> = items.iter().map(|item| preselected.contains(item)).collect();
+    let mut cursor = 0;
+    let mut drawn = 0;
+
+    term.hide_cursor()?;
+    let _cursor = ShowCursorOnDrop(&term);
+    let result = loop {
+        let matches = names::fuzzy_rank(items, &query);
+        cursor = cursor.min(matches.len().saturating_sub(1));
+
+        let count = chosen.iter().filter(|on| **on).count();
+        let mut lines = vec![format!(
+            "{} {} {}",
+            style(format!("{prompt}:")).bold(),
+            query,
+            style(format!("({count} selected)")).dim()
+        )];
+        let start = cursor.saturating_sub(VISIBLE_ROWS - 1);
+        for (row, &idx) in matches.iter().enumerate().skip(start).take(VISIBLE_ROWS) {
+            let mark = if chosen[idx] { "[x]" } else { "[ ]" };
+            let mut line = format!("{mark} {}", items[idx]);
+            if let Some(description) = &descriptions[idx] {
+                // Leave room for the cursor column; a wrapped row would break redrawing.
+                let room = width.saturating_sub(line.chars().count() + 6);
+                if room > 10 {
+                    let text: String = description.chars().take(room).collect();
+                    line = format!("{line}  {}", style(text).dim());
+                }
+            }
+            lines.push(if row == cursor {
+                format!("{} {}", style(">").cyan(), style(line).cyan())
+            } else {
+                format!("  {line}")
+            });
+        }
+        lines.push(style("─".repeat(40)).dim().to_string());
+        let highlighted = matches.get(cursor).map(|&idx| items[idx].as_str());
+        let body = highlighted
+            .map(|name| preview(name).unwrap_or_else(|| "(not cached yet)".to_string()))
+            .unwrap_or_default();
+        lines.extend(
+            body.lines()
+                .take(PREVIEW_LINES)
+                .map(|line| style(line).dim().to_string()),
+        );
+
+        term.clear_last_lines(drawn)?;
+        for line in &lines {
+            term.write_line(line)?;
+        }
+        drawn = lines.len();
+
+        match term.read_key()? {
+            Key::Escape => break Err("Selection cancelled".into()),
+            Key::Enter => {
+                let mut selected: Vec<String> = items
+                    .iter()
+                    .zip(&chosen)
+                    .filter(|(_, on)| **on)
+                    .map(|(item, _)| item.clone())
+                    .collect();
+                if selected.is_empty() {
+                    selected.extend(highlighted.map(str::to_string));
+                }
+                if !selected.is_empty() {
+                    break Ok(selected);
+                }
+            }
+            Key::Tab | Key::Char(' ') => {
+                if let Some(&idx) = matches.get(cursor) {
+                    chosen[idx] = !chosen[idx];
+                }
+            }
+            Key::ArrowUp => cursor = cursor.saturating_sub(1),
+            Key::ArrowDown => cursor += 1,
+            Key::Backspace => {
+                query.pop();
+                cursor = 0;
+            }
+            Key::Char(c) if !c.is_control() => {
+                query.push(c);
+                cursor = 0;
+            }
+            _ => {}
+        }
+    };
+    term.clear_last_lines(drawn)?;
+    result
+}
+
+/// Brings the cursor back however the picker is left, including a terminal error.
+struct ShowCursorOnDrop<'a>(&'a Term);
+
+impl Drop for ShowCursorOnDrop<'_> {
+    fn drop(&mut self) {
+        let _ = self.0.show_cursor();
+    }
+}
@@ -0,0 +1,40 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+use std::time::Duration;
+
+use crate::verbosity;
+
+const TICK: Duration = Duration::from_millis(100);
+
+/// Progress goes to stderr, which indicatif hides when it isn't a terminal; `-q` hides it too.
+fn target() -> ProgressDrawTarget {
+    if verbosity::is_quiet() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// Spinner for a single request of unknown duration; cleared when dropped.
+pub fn spinner(message: &str) -> ProgressBar {
+    let spinner = ProgressBar::with_draw_target(None, target())
+        .with_style(ProgressStyle::with_template("{spinner} {msg}").expect("valid template"))
+        .with_message(message.to_string())
+        .with_finish(ProgressFinish::AndClear);
+    spinner.enable_steady_tick(TICK);
+    spinner
+}
+
+/// Bar over `len` items, cleared when dropped; set the current item with `set_message`.
+/// A single item gets no bar.
+pub fn bar(len: usize, prefix: &str) -> ProgressBar {
+    if len < 2 {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} {msg}")
+        .expect("valid template")
+        .progress_chars("=> ");
+    ProgressBar::with_draw_target(Some(len as u64), target())
+        .with_style(style)
+        .with_prefix(prefix.to_string())
+        .with_finish(ProgressFinish::AndClear)
+}
@@ -0,0 +1,65 @@
+use serde::Deserialize;
+
+/// Local edits applied to a template every time it is fetched, configured
+/// per template under `[rules.<Name>]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateRules {
+    /// Commented-out patterns to enable (e.g. `Cargo.lock`).
+    pub uncomment: Vec<String>,
+    /// Patterns to disable by commenting them out.
+    pub comment: Vec<String>,
+    /// Patterns to drop entirely.
+    pub remove: Vec<String>,
+    /// Extra lines to add at the end of the template.
+    pub append: Vec<String>,
+}
+
+impl TemplateRules {
+    pub fn apply(&self, content: &str) -> String {
+        let mut out = String::new();
+        for line in content.lines() {
+            let pattern = line.trim();
+            if self.remove.iter().any(|p| p == pattern) {
+                continue;
+            }
+            if self.comment.iter().any(|p| p == pattern) {
+                out.push_str(&format!("# {pattern}\n"));
+                continue;
+            }
+            let uncommented = pattern.strip_prefix('#').map(str::trim);
+            match uncommented {
+                Some(inner) if self.uncomment.iter().any(|p| p == inner) => {
+                    out.push_str(inner);
+                }
+                _ => out.push_str(line),
+            }
+            out.push('\n');
+        }
+        for line in &self.append {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_rules_to_template() {
+        let rules = TemplateRules {
+            uncomment: vec!["Cargo.lock".into()],
+            comment: vec!["*.pdb".into()],
+            remove: vec![".env".into()],
+            append: vec!["/secrets".into()],
+        };
+        let content = "target/\n# Cargo.lock\n*.pdb\n.env\n";
+        assert_eq!(
+            rules.apply(content),
+            "target/\nCargo.lock\n# *.pdb\n/secrets\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::names;
+use crate::DynError;
+
+/// Prefix that marks a snippet on the command line (`+locks`).
+pub const PREFIX: char = '+';
+/// Long form accepted in `include` lists (`snippet:locks`).
+const INCLUDE_PREFIX: &str = "snippet:";
+
+const EXTENSION: &str = "gitignore";
+
+/// Snippets available without any configuration.
+const BUILTIN_SNIPPETS: &[(&str, &str)] = &[
+    ("locks", "# Lock Files\npackage-lock.json\nyarn.lock\n"),
+    ("macos", "# Desktop Service Store Mac\n.DS_Store\n"),
+];
+
+/// A snippet as written in config: either its body, or a body layered on other templates.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnippetDef {
+    Body(String),
+    Composed {
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        content: String,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(from = "SnippetDef")]
+pub struct Snippet {
+    /// Templates (`Node`) and snippets (`snippet:locks` or `+locks`) emitted before `body`.
+    pub include: Vec<String>,
+    pub body: String,
+}
+
+impl Snippet {
+    pub fn plain(body: &str) -> Self {
+        Self {
+            include: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+}
+
+impl From<SnippetDef> for Snippet {
+    fn from(def: SnippetDef) -> Self {
+        match def {
+            SnippetDef::Body(body) => Self {
+                include: Vec::new(),
+                body,
+            },
+            SnippetDef::Composed { include, content } => Self {
+                include,
+                body: content,
+            },
+        }
+    }
+}
+
+/// Small user-defined blocks applied like templates but never fetched.
+#[derive(Debug, Default)]
+pub struct Snippets {
+    snippets: BTreeMap<String, Snippet>,
+}
+
+impl Snippets {
+    /// Built-ins, overridden by `<name>.gitignore` files in `dir`, overridden by `configured`.
+    pub fn load(configured: &BTreeMap<String, Snippet>, dir: &Path) -> Result<Self, DynError> {
+        let mut snippets: BTreeMap<String, Snippet> = BUILTIN_SNIPPETS
+            .iter()
+            .map(|(name, body)| (name.to_string(), Snippet::plain(body)))
+            .collect();
+        if dir.is_dir() {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_none_or(|ext| ext != EXTENSION) {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                let body = fs::read_to_string(&path)
+                    .map_err(|err| format!("Cannot read {}: {err}", path.display()))?;
+                snippets.insert(name.to_string(), Snippet::plain(&body));
+            }
+        }
+        snippets.extend(configured.iter().map(|(name, snippet)| (name.clone(), snippet.clone())));
+        Ok(Self { snippets })
+    }
+
+    /// Adds `more`, shadowing snippets of the same name.
+    pub fn extend(&mut self, more: &BTreeMap<String, Snippet>) {
+        self.snippets.extend(more.iter().map(|(name, snippet)| (name.clone(), snippet.clone())));
+    }
+
+    /// The snippet invoked as `input` (`+name`), or `None` if `input` isn't one.
+    pub fn get(&self, input: &str) -> Option<Result<&Snippet, DynError>> {
+        let name = input.strip_prefix(PREFIX)?;
+        Some(
+            self.snippets
+                .iter()
+                .find(|(known, _)| names::eq_folded(known, name))
+                .map(|(_, snippet)| snippet)
+                .ok_or_else(|| {
+                    format!("Unknown snippet {input} (known: {})", self.names().join(", ")).into()
+                }),
+        )
+    }
+
+    /// Snippet names as typed on the command line, e.g. `+locks`.
+    pub fn names(&self) -> Vec<String> {
+        self.snippets.keys().map(|name| format!("{PREFIX}{name}")).collect()
+    }
+}
+
+/// Rewrites `snippet:name` entries of an `include` list to the `+name` form.
+pub fn include_target(entry: &str) -> String {
+    match entry.strip_prefix(INCLUDE_PREFIX) {
+        Some(name) => format!("{PREFIX}{name}"),
+        None => entry.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_builtin_and_configured_snippets() {
+        let configured = BTreeMap::from([
+            ("env".to_string(), Snippet::plain(".env\n")),
+            ("locks".to_string(), Snippet::plain("Cargo.lock\n")),
+        ]);
+        let snippets = Snippets::load(&configured, Path::new("/nonexistent")).unwrap();
+
+        assert_eq!(snippets.names(), vec!["+env", "+locks", "+macos"]);
+        assert_eq!(snippets.get("+Env").unwrap().unwrap().body, ".env\n");
+        assert_eq!(snippets.get("+locks").unwrap().unwrap().body, "Cargo.lock\n");
+        assert!(snippets.get("Rust").is_none());
+        assert_eq!(
+            snippets.get("+nope").unwrap().unwrap_err().to_string(),
+            "Unknown snippet +nope (known: +env, +locks, +macos)"
+        );
+    }
+
+    #[test]
+    fn normalizes_include_targets() {
+        assert_eq!(include_target("snippet:locks"), "+locks");
+        assert_eq!(include_target("+locks"), "+locks");
+        assert_eq!(include_target("Node"), "Node");
+    }
+}
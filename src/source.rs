@@ -0,0 +1,283 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+const API_BASE_URL: &str = "https://api.github.com";
+const RAW_BASE_URL: &str = "https://raw.githubusercontent.com";
+
+/// The token sent to github.com's API; a GitHub Enterprise Server's takes its own.
+const GITHUB_TOKEN_VAR: &str = "GITHUB_TOKEN";
+const ENTERPRISE_TOKEN_VAR: &str = "GH_ENTERPRISE_TOKEN";
+
+/// The environment variable holding the token for the API at `url`, so a github.com
+/// token never reaches another host or the other way around.
+pub fn token_var(url: &str) -> &'static str {
+    let rest = url.strip_prefix(API_BASE_URL).unwrap_or("-");
+    if rest.is_empty() || rest.starts_with('/') {
+        GITHUB_TOKEN_VAR
+    } else {
+        ENTERPRISE_TOKEN_VAR
+    }
+}
+
+/// Everything but RFC 3986's unreserved characters, so `C++` and `F#` survive the trip
+/// (`+` would otherwise read as a space to some servers, `#` as a fragment).
+const PATH_SEGMENT: &AsciiSet =
+    &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// A GitHub repository and ref hosting `.gitignore` templates, written
+/// `owner/repo[@ref]` (e.g. `github/gitignore@main` or a company fork).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Source {
+    pub owner: String,
+    pub repo: String,
+    pub reference: String,
+    /// Set from `--github-host` and friends; not part of the `owner/repo@ref` spelling.
+    pub hosts: Hosts,
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self {
+            owner: "github".to_string(),
+            repo: "gitignore".to_string(),
+            reference: "master".to_string(),
+            hosts: Hosts::default(),
+        }
+    }
+}
+
+/// Base URLs of the API and of raw file downloads: github.com's, or a GitHub Enterprise
+/// Server's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hosts {
+    pub api: String,
+    pub raw: String,
+}
+
+impl Default for Hosts {
+    fn default() -> Self {
+        Self {
+            api: API_BASE_URL.to_string(),
+            raw: RAW_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl Hosts {
+    /// A GHES instance such as `github.example.com`, which serves the API under `/api/v3`
+    /// and raw files under `/raw`. A scheme (`http://...`) is kept if given.
+    pub fn enterprise(host: &str) -> Self {
+        let host = host.trim_end_matches('/');
+        let base = if host.contains("://") { host.to_string() } else { format!("https://{host}") };
+        Self {
+            api: format!("{base}/api/v3"),
+            raw: format!("{base}/raw"),
+        }
+    }
+
+    pub fn is_github(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A directory name for caching per host (`ghe.example.com_api_v3`).
+    pub fn cache_dir_name(&self) -> String {
+        let api = self.api.split_once("://").map_or(self.api.as_str(), |(_, rest)| rest);
+        api.replace(['/', ':'], "_")
+    }
+}
+
+impl Source {
+    /// Every file in the repository at `reference`, subdirectories included.
+    pub fn tree_url(&self) -> String {
+        format!(
+            "{}/repos/{}/{}/git/trees/{}?recursive=1",
+            self.hosts.api,
+            self.owner,
+            self.repo,
+            self.encoded_reference()
+        )
+    }
+
+    pub fn commits_url(&self, path: &str) -> String {
+        format!(
+            "{}/repos/{}/{}/commits?path={}&sha={}&per_page=1",
+            self.hosts.api,
+            self.owner,
+            self.repo,
+            encode_path(path),
+            self.encoded_reference()
+        )
+    }
+
+    /// Answers with the commit SHA `reference` points at, given `Accept: ...sha`.
+    pub fn commit_url(&self) -> String {
+        format!(
+            "{}/repos/{}/{}/commits/{}",
+            self.hosts.api,
+            self.owner,
+            self.repo,
+            self.encoded_reference()
+        )
+    }
+
+    pub fn raw_url(&self, name: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/{}.gitignore",
+            self.hosts.raw,
+            self.owner,
+            self.repo,
+            self.encoded_reference(),
+            encode_path(name)
+        )
+    }
+
+    /// `reference` as one URL segment, so a branch such as `feature/x` stays one.
+    fn encoded_reference(&self) -> String {
+        utf8_percent_encode(&self.reference, PATH_SEGMENT).to_string()
+    }
+}
+
+/// Percent-encodes each `/`-separated segment of a template path.
+fn encode_path(path: &str) -> String {
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect();
+    segments.join("/")
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (repo_path, reference) = match value.split_once('@') {
+            Some((path, reference)) => (path, reference.to_string()),
+            None => (value, Self::default().reference),
+        };
+        let valid = |part: &str| !part.is_empty() && !part.contains('/');
+        match repo_path.split_once('/') {
+            Some((owner, repo)) if valid(owner) && valid(repo) && !reference.is_empty() => {
+                Ok(Self {
+                    owner: owner.to_string(),
+                    repo: repo.to_string(),
+                    reference,
+                    hosts: Hosts::default(),
+                })
+            }
+            _ => Err(format!("Invalid source '{value}' (expected OWNER/REPO[@REF])")),
+        }
+    }
+}
+
+impl TryFrom<String> for Source {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}@{}", self.owner, self.repo, self.reference)?;
+        // Naming the host keeps caches of same-named GHES and github.com repos apart.
+        if !self.hosts.is_github() {
+            write!(f, " ({})", self.hosts.api)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_token_for_each_host() {
+        assert_eq!(token_var("https://api.github.com/repos/a/b"), "GITHUB_TOKEN");
+        assert_eq!(token_var("https://api.github.com.evil.example/x"), "GH_ENTERPRISE_TOKEN");
+        assert_eq!(token_var("https://ghe.example.com/api/v3/repos/a/b"), "GH_ENTERPRISE_TOKEN");
+    }
+
+    #[test]
+    fn parses_sources_and_builds_urls() {
+        let source: Source = "acme/gitignore@main".parse().unwrap();
+        assert_eq!(
+            source.raw_url("Global/macOS"),
+            "https://raw.githubusercontent.com/acme/gitignore/main/Global/macOS.gitignore"
+        );
+        assert_eq!(
+            source.tree_url(),
+            "https://api.github.com/repos/acme/gitignore/git/trees/main?recursive=1"
+        );
+
+        let enterprise = Source { hosts: Hosts::enterprise("ghe.example.com/"), ..source };
+        assert_eq!(
+            enterprise.raw_url("Rust"),
+            "https://ghe.example.com/raw/acme/gitignore/main/Rust.gitignore"
+        );
+        assert_eq!(
+            enterprise.tree_url(),
+            "https://ghe.example.com/api/v3/repos/acme/gitignore/git/trees/main?recursive=1"
+        );
+        assert_eq!(
+            enterprise.to_string(),
+            "acme/gitignore@main (https://ghe.example.com/api/v3)"
+        );
+
+        let default: Source = "github/gitignore".parse().unwrap();
+        assert_eq!(default, Source::default());
+        assert!("gitignore".parse::<Source>().is_err());
+        assert!("a/b/c".parse::<Source>().is_err());
+    }
+
+    #[test]
+    fn encodes_special_characters_in_names() {
+        let source = Source::default();
+        let raw = |name| source.raw_url(name).replace("https://raw.githubusercontent.com/", "");
+        assert_eq!(raw("C++"), "github/gitignore/master/C%2B%2B.gitignore");
+        assert_eq!(raw("F#"), "github/gitignore/master/F%23.gitignore");
+        assert_eq!(raw("Objective-C"), "github/gitignore/master/Objective-C.gitignore");
+        assert_eq!(
+            raw("community/Q# (QDK)"),
+            "github/gitignore/master/community/Q%23%20%28QDK%29.gitignore"
+        );
+        assert!(source.commits_url("C++.gitignore").contains("?path=C%2B%2B.gitignore&"));
+    }
+
+    #[test]
+    fn every_special_character_template_round_trips() {
+        // Names needing more than letters, digits, `-`, `.` and `_`: upstream's `+` and `#`
+        // (C++, F#), and the spaces, punctuation and non-ASCII a fork may add.
+        let names = ["C++", "F#", "community/Q# (QDK)", "Global/A&B", "Notes?", "100%", "Ünicode"];
+        let source = Source::default();
+        for name in names {
+            let url = source.raw_url(name);
+            let path = url.strip_prefix("https://raw.githubusercontent.com/").unwrap();
+            assert!(
+                path.chars().all(|c| c.is_ascii_alphanumeric() || "-._~/%".contains(c)),
+                "{url}"
+            );
+            let decoded = percent_encoding::percent_decode_str(path).decode_utf8().unwrap();
+            assert_eq!(decoded, format!("github/gitignore/master/{name}.gitignore"));
+        }
+    }
+
+    #[test]
+    fn encodes_the_ref() {
+        let source: Source = "acme/gitignore@feature/x".parse().unwrap();
+        assert_eq!(
+            source.tree_url(),
+            "https://api.github.com/repos/acme/gitignore/git/trees/feature%2Fx?recursive=1"
+        );
+        assert_eq!(
+            source.raw_url("Rust"),
+            "https://raw.githubusercontent.com/acme/gitignore/feature%2Fx/Rust.gitignore"
+        );
+        assert!(source.commit_url().ends_with("/commits/feature%2Fx"));
+        assert!(source.commits_url("Rust.gitignore").contains("&sha=feature%2Fx&"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::DynError;
+
+/// Subversion ignore properties equivalent to a gitignore file (`export-svn`).
+#[derive(Debug, Default, PartialEq)]
+pub struct Properties {
+    /// `svn:global-ignores` on the root, inherited by every directory (Subversion 1.8+).
+    pub global_ignores: Vec<String>,
+    /// `svn:ignore` per directory, which only covers that directory's own entries.
+    pub ignores: BTreeMap<String, Vec<String>>,
+    /// Patterns Subversion can't express: negations and wildcard directories.
+    pub untranslated: Vec<String>,
+}
+
+impl Properties {
+    /// Sorts gitignore patterns by where Subversion needs them: names matched at any depth
+    /// go to `svn:global-ignores`, paths to `svn:ignore` on their parent directory.
+    pub fn convert(gitignore: &str) -> Self {
+        let mut properties = Properties::default();
+        for line in gitignore.lines() {
+            let pattern = line.trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+            // Subversion has no directory-only patterns; `build/` ignores `build` either way.
+            let path = pattern.trim_end_matches('/');
+            let unsupported = pattern.starts_with('!') || path.contains("**") || path.is_empty();
+            let (dir, name) = match path.trim_start_matches('/').rsplit_once('/') {
+                _ if unsupported => {
+                    properties.untranslated.push(pattern.to_string());
+                    continue;
+                }
+                Some((dir, name)) if !dir.contains(['*', '?', '[']) => (dir, name),
+                Some(_) => {
+                    properties.untranslated.push(pattern.to_string());
+                    continue;
+                }
+                None if path.starts_with('/') => (".", &path[1..]),
+                None => {
+                    push_unique(&mut properties.global_ignores, path);
+                    continue;
+                }
+            };
+            push_unique(properties.ignores.entry(dir.to_string()).or_default(), name);
+        }
+        properties
+    }
+
+    /// The properties as `svn propget` would show them, one block each.
+    pub fn render(&self) -> String {
+        let mut blocks = Vec::new();
+        if !self.global_ignores.is_empty() {
+            blocks.push(format!("svn:global-ignores on .\n{}\n", self.global_ignores.join("\n")));
+        }
+        for (dir, names) in &self.ignores {
+            blocks.push(format!("svn:ignore on {dir}\n{}\n", names.join("\n")));
+        }
+        blocks.join("\n")
+    }
+
+    /// Sets every property with `svn propset` in the working copy at `root`, replacing
+    /// the values there.
+    pub fn apply(&self, root: &Path) -> Result<(), DynError> {
+        if !self.global_ignores.is_empty() {
+            propset(root, "svn:global-ignores", &self.global_ignores, ".")?;
+        }
+        for (dir, names) in &self.ignores {
+            propset(root, "svn:ignore", names, dir)?;
+        }
+        Ok(())
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|known| known == value) {
+        values.push(value.to_string());
+    }
+}
+
+fn propset(root: &Path, name: &str, values: &[String], dir: &str) -> Result<(), DynError> {
+    let value = values.join("\n") + "\n";
+    let status = Command::new("svn")
+        .args(["propset", name, &value, dir])
+        .current_dir(root)
+        .status()
+        .map_err(|err| format!("Cannot run svn: {err}"))?;
+    if !status.success() {
+        return Err(format!("svn propset {name} on {dir} failed ({status})").into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_global_and_per_directory_ignores() {
+        let gitignore =
+            "# Build\n*.log\ntarget/\n/dist\ndocs/_build/\n*.log\n!keep.log\nsrc/**/gen\n";
+        let properties = Properties::convert(gitignore);
+        assert_eq!(properties.global_ignores, ["*.log", "target"]);
+        assert_eq!(properties.ignores["."], ["dist"]);
+        assert_eq!(properties.ignores["docs"], ["_build"]);
+        assert_eq!(properties.untranslated, ["!keep.log", "src/**/gen"]);
+        assert_eq!(
+            properties.render(),
+            "svn:global-ignores on .\n*.log\ntarget\n\nsvn:ignore on .\ndist\n\n\
+             svn:ignore on docs\n_build\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::snippets::Snippet;
+use crate::{DynError, DEFAULT_OUTPUT};
+
+/// The per-repository manifest `sync` reads by default.
+pub const MANIFEST_FILE: &str = ".gitignore.toml";
+
+/// A repository's declared ignore setup (`.gitignore.toml`), which `sync` makes the
+/// ignore file match.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// File to generate, relative to the manifest (default `.gitignore`).
+    pub output: Option<PathBuf>,
+    /// Templates, `+snippets` and `@profiles`, in the order their sections should appear.
+    pub templates: Vec<String>,
+    /// Snippets only this repository uses, shadowing configured ones of the same name.
+    pub snippets: BTreeMap<String, Snippet>,
+    /// Template name → the source ref (commit, tag or branch) to fetch it at.
+    pub pins: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, DynError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("Invalid {}: {err}", path.display()).into())
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, DynError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// The ignore file the manifest at `path` describes.
+    pub fn output_path(&self, path: &Path) -> PathBuf {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        dir.join(self.output.as_deref().unwrap_or(Path::new(DEFAULT_OUTPUT)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_manifests() {
+        let manifest = Manifest::parse(
+            r#"
+output = "app/.gitignore"
+templates = ["Rust", "+env"]
+
+[snippets]
+env = ".env\n"
+
+[pins]
+Rust = "v1.0"
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.templates, ["Rust", "+env"]);
+        assert_eq!(manifest.snippets["env"].body, ".env\n");
+        assert_eq!(manifest.pins["Rust"], "v1.0");
+        assert_eq!(
+            manifest.output_path(Path::new("repo/.gitignore.toml")),
+            Path::new("repo/app/.gitignore")
+        );
+        let default = Manifest::default().output_path(Path::new(MANIFEST_FILE));
+        assert_eq!(default, Path::new(".gitignore"));
+        assert!(Manifest::parse("target = \"x\"").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dockerignore;
+use crate::document::{parse_section_header, WRITTEN_WITH};
+use crate::section_header;
+
+/// Whether `line` is a pattern rather than a comment or blank line.
+fn is_pattern(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Drops pattern lines an earlier template already contributed, leaving a
+/// `# <pattern> (see <Name>)` comment in their place (`--dedupe-lines`).
+#[derive(Debug, Default)]
+pub struct LineDeduper {
+    first_seen: HashMap<String, String>,
+}
+
+impl LineDeduper {
+    pub fn apply(&mut self, name: &str, content: &str) -> String {
+        let mut out = String::new();
+        for line in content.lines() {
+            if is_pattern(line) {
+                let pattern = line.trim();
+                match self.first_seen.get(pattern) {
+                    Some(first) if first != name => {
+                        out.push_str(&format!("# {pattern} (see {first})\n"));
+                        continue;
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.first_seen.insert(pattern.to_string(), name.to_string());
+                    }
+                }
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Joins the templates' names into the name of their merged section (`Rust + Node`).
+pub const MERGED_SEPARATOR: &str = " + ";
+
+/// The templates a section written with `--dedupe-lines` merges, from its name.
+pub fn merged_parts(name: &str) -> Vec<&str> {
+    name.split(MERGED_SEPARATOR).collect()
+}
+
+/// `parts` (name and content) as one block for `--dedupe-lines`: each under a
+/// `# From <Name>` comment, with the patterns an earlier one added noted instead.
+pub fn merge<'a>(parts: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut deduper = LineDeduper::default();
+    let blocks: Vec<String> = parts
+        .into_iter()
+        .map(|(name, content)| {
+            let content = deduper.apply(name, content);
+            format!("# From {name}\n{}", content.trim_end_matches('\n'))
+        })
+        .collect();
+    blocks.join("\n\n") + "\n"
+}
+
+/// Sorts each run of patterns in place (`--sort`). Comments, blank lines and `!`
+/// negations stay where they are, since what a negation re-includes depends on order.
+pub fn sort_patterns(content: &str) -> String {
+    let mut out = String::new();
+    let mut run: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if is_pattern(line) && !line.trim_start().starts_with('!') {
+            run.push(line);
+            continue;
+        }
+        flush_sorted(&mut out, &mut run);
+        out.push_str(line);
+        out.push('\n');
+    }
+    flush_sorted(&mut out, &mut run);
+    out
+}
+
+fn flush_sorted(out: &mut String, run: &mut Vec<&str>) {
+    run.sort_unstable();
+    for line in run.drain(..) {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Keeps only pattern lines (`--minify`).
+pub fn minify(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| is_pattern(line))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// The options that rewrite template content. They're recorded under the section header
+/// (`# Written with: --minify`), so `--check` and `diff` can rewrite upstream the same way
+/// before comparing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Transforms {
+    /// The section merges several templates (see `merge`).
+    pub dedupe_lines: bool,
+    /// Patterns rewritten for Docker (see `dockerignore::translate`).
+    pub dockerignore: bool,
+    pub sort: bool,
+    pub minify: bool,
+}
+
+impl Transforms {
+    /// The options named in `flags`, the rest of a `# Written with:` line.
+    pub fn parse(flags: &str) -> Self {
+        let mut transforms = Transforms::default();
+        for flag in flags.split_whitespace() {
+            match flag {
+                "--dedupe-lines" => transforms.dedupe_lines = true,
+                "--target=dockerignore" => transforms.dockerignore = true,
+                "--sort" => transforms.sort = true,
+                "--minify" => transforms.minify = true,
+                _ => {}
+            }
+        }
+        transforms
+    }
+
+    /// The `# Written with:` line recording these options; `None` if there are none.
+    pub fn comment(&self) -> Option<String> {
+        let flags = [
+            (self.dedupe_lines, "--dedupe-lines"),
+            (self.dockerignore, "--target=dockerignore"),
+            (self.sort, "--sort"),
+            (self.minify, "--minify"),
+        ];
+        let flags: Vec<&str> = flags
+            .into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect();
+        (!flags.is_empty()).then(|| format!("{WRITTEN_WITH}{}\n", flags.join(" ")))
+    }
+
+    pub fn apply(&self, content: &str) -> String {
+        let mut content = content.to_string();
+        if self.dockerignore {
+            content = dockerignore::translate(&content);
+        }
+        if self.sort {
+            content = sort_patterns(&content);
+        }
+        if self.minify {
+            content = minify(&content);
+        }
+        content
+    }
+}
+
+/// Sections once written by the `--macos` and `--locks` flags, now the `+macos` and
+/// `+locks` snippets.
+const RENAMED_SECTIONS: &[(&str, &str)] = &[("--macos", "+macos"), ("--locks", "+locks")];
+
+/// Tidies a hand-edited file (`normalize`): strips trailing whitespace, collapses runs of
+/// blank lines, drops repeated patterns, rewrites `### Name ###`-style headers as
+/// `# --- Name ---` and renames sections of removed flags.
+pub fn normalize(content: &str) -> String {
+    let mut out = String::new();
+    let mut seen = HashSet::new();
+    let mut blank = true;
+    for line in content.lines() {
+        let line = trim_trailing(line);
+        if line.is_empty() {
+            if !blank {
+                out.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+        if line.starts_with('!') {
+            // A later repeat may re-exclude what this negation brought back.
+            seen.clear();
+        } else if is_pattern(line) && !seen.insert(line) {
+            continue;
+        }
+        match parse_section_header(line).map(renamed).or_else(|| legacy_header(line)) {
+            Some(name) => out.push_str(&section_header(name)),
+            None => out.push_str(line),
+        }
+        out.push('\n');
+        blank = false;
+    }
+    if blank && out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// The name a section written as `name` goes by now.
+fn renamed(name: &str) -> &str {
+    RENAMED_SECTIONS.iter().find(|(old, _)| *old == name).map_or(name, |(_, new)| new)
+}
+
+/// Trailing whitespace is ignored by git unless escaped, so `foo\ ` keeps one space.
+fn trim_trailing(line: &str) -> &str {
+    let trimmed = line.trim_end();
+    if trimmed.ends_with('\\') && line.len() > trimmed.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+/// The name in a decorated comment such as `### Rust ###`, `#--- Rust ---` or
+/// `# === Rust ===`.
+fn legacy_header(line: &str) -> Option<&str> {
+    let body = line.strip_prefix('#')?.trim();
+    let decorated = |s: &str| ["##", "--", "=="].iter().any(|mark| s.starts_with(mark));
+    let reversed: String = body.chars().rev().collect();
+    if !decorated(body) || !decorated(&reversed) {
+        return None;
+    }
+    let name = body.trim_matches(|c: char| matches!(c, '#' | '-' | '=') || c.is_whitespace());
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupes_patterns_across_templates() {
+        let mut deduper = LineDeduper::default();
+        assert_eq!(deduper.apply("Rust", "# Logs\n*.log\ntarget/\n"), "# Logs\n*.log\ntarget/\n");
+        assert_eq!(
+            deduper.apply("Node", "# Logs\n*.log\nnode_modules/\n"),
+            "# Logs\n# *.log (see Rust)\nnode_modules/\n"
+        );
+    }
+
+    #[test]
+    fn merges_templates_into_one_block() {
+        let merged = merge([("Rust", "target/\n*.log\n\n"), ("Node", "# Logs\n*.log\n")]);
+        assert_eq!(
+            merged,
+            "# From Rust\ntarget/\n*.log\n\n# From Node\n# Logs\n# *.log (see Rust)\n"
+        );
+        assert_eq!(merged_parts("Rust + +logs"), ["Rust", "+logs"]);
+    }
+
+    #[test]
+    fn sorts_patterns_within_groups() {
+        let content = "# Build\ntarget/\ndebug/\n\n# Logs\n*.log\nlogs\n!keep.log\nb\na\n";
+        assert_eq!(
+            sort_patterns(content),
+            "# Build\ndebug/\ntarget/\n\n# Logs\n*.log\nlogs\n!keep.log\na\nb\n"
+        );
+    }
+
+    #[test]
+    fn normalizes_hand_edited_files() {
+        let content =
+            "\n### Rust ###\ntarget/  \nkeep\\ \n\n\n\n*.log\ntarget/\n!target/keep\ntarget/\n\n";
+        assert_eq!(
+            normalize(content),
+            "# --- Rust ---\ntarget/\nkeep\\ \n\n*.log\n!target/keep\ntarget/\n"
+        );
+        assert_eq!(legacy_header("# Logs"), None);
+        assert_eq!(legacy_header("# ----------"), None);
+        assert_eq!(legacy_header("#=== Node ==="), Some("Node"));
+    }
+
+    #[test]
+    fn renames_sections_of_removed_flags() {
+        let content = "# --- --macos ---\n.DS_Store\n\n# --- --locks ---\nyarn.lock\n";
+        assert_eq!(
+            normalize(content),
+            "# --- +macos ---\n.DS_Store\n\n# --- +locks ---\nyarn.lock\n"
+        );
+        assert_eq!(normalize("# --- Rust ---\ntarget/\n"), "# --- Rust ---\ntarget/\n");
+    }
+
+    #[test]
+    fn records_transforms_in_a_comment() {
+        let both = Transforms { sort: true, minify: true, ..Transforms::default() };
+        assert_eq!(both.comment().as_deref(), Some("# Written with: --sort --minify\n"));
+        assert_eq!(Transforms::parse("--minify --sort"), both);
+        assert_eq!(Transforms::parse("").comment(), None);
+        assert_eq!(both.apply("# Logs\n*.log\n*.bak\n"), "*.bak\n*.log\n");
+    }
+
+    #[test]
+    fn minify_keeps_only_patterns() {
+        let content = "# Logs\n*.log\n\n  \n\\#literal\n!keep.log\n";
+        assert_eq!(minify(content), "*.log\n\\#literal\n!keep.log\n");
+    }
+}
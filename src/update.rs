@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::env::consts::{ARCH, EXE_SUFFIX, OS};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often normal runs may ask GitHub about new releases.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Where releases of this tool are published.
+pub fn latest_release_url() -> String {
+    let repo = env!("CARGO_PKG_REPOSITORY").trim_start_matches("https://github.com/");
+    format!("https://api.github.com/repos/{repo}/releases/latest")
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// The last release check, kept in the cache so runs ask at most once a day.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LastCheck {
+    pub checked_at: u64,
+    /// Latest version seen then; the running version if the check failed.
+    pub latest: String,
+}
+
+impl LastCheck {
+    pub fn now(latest: &str) -> Self {
+        let checked_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        LastCheck { checked_at, latest: latest.to_string() }
+    }
+
+    pub fn is_due(&self) -> bool {
+        let checked = UNIX_EPOCH + Duration::from_secs(self.checked_at);
+        checked.elapsed().is_ok_and(|age| age >= CHECK_INTERVAL)
+    }
+}
+
+/// The release artifact for this platform, e.g. `gi-x86_64-linux` or `gi-aarch64-macos`.
+pub fn artifact_name() -> String {
+    format!("gi-{ARCH}-{OS}{EXE_SUFFIX}")
+}
+
+/// Whether `latest` is a later version than `current`, comparing dotted numbers
+/// (`1.10.0` > `1.9.2`); anything after a `-` is ignored.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        let version = version.trim_start_matches('v');
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        core.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+    };
+    parts(latest) > parts(current)
+}
+
+/// The digest for `artifact` in a `sha256sum`-style checksum file, or in a file holding
+/// just the digest.
+pub fn expected_sha256(checksums: &str, artifact: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let digest = fields.next()?;
+        let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+        let named = match fields.next() {
+            Some(file) => file.trim_start_matches('*') == artifact,
+            None => true,
+        };
+        (valid && named).then(|| digest.to_ascii_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("v1.10.0", "1.9.2"));
+        assert!(is_newer("1.0.1", "1.0.0"));
+        assert!(!is_newer("v1.0.0", "1.0.0"));
+        assert!(!is_newer("1.0.0-rc.1", "1.0.0"));
+        assert!(!is_newer("0.9", "1.0.0"));
+    }
+
+    #[test]
+    fn checks_at_most_daily() {
+        assert!(!LastCheck::now("1.0.0").is_due());
+        assert!(LastCheck { checked_at: 0, latest: "1.0.0".into() }.is_due());
+    }
+    #[test]
+    fn finds_the_artifact_checksum() {
+        let digest = "a".repeat(64);
+        let listing = format!("{}  gi-x86_64-macos\n{digest} *gi-x86_64-linux\n", "b".repeat(64));
+        assert_eq!(expected_sha256(&listing, "gi-x86_64-linux"), Some(digest.clone()));
+        assert_eq!(expected_sha256(&format!("{digest}\n"), "gi-x86_64-linux"), Some(digest));
+        assert_eq!(expected_sha256("not a digest  gi-x86_64-linux", "gi-x86_64-linux"), None);
+    }
+}
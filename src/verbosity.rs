@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Silences progress messages for the rest of the process (`-q`).
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Moves `status!` to stderr, for when stdout carries the result (`--output -`).
+pub fn status_to_stderr() {
+    STATUS_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn status_on_stderr() -> bool {
+    STATUS_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// Progress on stdout ("Appended Rust"), silenced by `-q`.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::verbosity::is_quiet() {
+            // silenced
+        } else if $crate::verbosity::status_on_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Side remarks on stderr ("Skipping Rust"), silenced by `-q`.
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if !$crate::verbosity::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,105 @@
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::DynError;
+
+/// Known-good SHA-256 digests keyed by template name (e.g. `Rust`, `Global/macOS`), from
+/// a manifest the user made with `sha256sum` over a checkout they trust.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    entries: HashMap<String, String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, DynError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read manifest {}: {err}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    /// Parses `sha256sum`-style lines: `<hex digest>  <Name>.gitignore`.
+    pub fn parse(contents: &str) -> Result<Self, DynError> {
+        let mut entries = HashMap::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (digest, file) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Malformed manifest line {}", idx + 1))?;
+            let digest = digest.to_ascii_lowercase();
+            if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid checksum on manifest line {}", idx + 1).into());
+            }
+            let name = file
+                .trim()
+                .trim_start_matches('*')
+                .trim_start_matches("./")
+                .trim_end_matches(".gitignore");
+            entries.insert(name.to_string(), digest);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn verify(&self, name: &str, content: &str) -> Result<(), DynError> {
+        let expected = self
+            .entries
+            .get(name)
+            .ok_or_else(|| format!("No known checksum for template '{name}'"))?;
+        let actual = sha256_hex(content.as_bytes());
+        if &actual != expected {
+            return Err(format!(
+                "Checksum mismatch for template '{name}' (expected {expected}, got {actual})"
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The id git gives `bytes` as a blob (`git hash-object`), which the trees API reports
+/// as each template's `sha`.
+pub fn git_blob_sha(bytes: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", bytes.len()));
+    hasher.update(bytes);
+    hasher.finalize().iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_against_manifest() {
+        let digest = sha256_hex(b"target/\n");
+        let manifest = Manifest::parse(&format!("# comment\n{digest}  Rust.gitignore\n")).unwrap();
+        assert!(manifest.verify("Rust", "target/\n").is_ok());
+        assert!(manifest.verify("Rust", "tampered/\n").is_err());
+        assert!(manifest.verify("Node", "node_modules/\n").is_err());
+    }
+
+    #[test]
+    fn hashes_blobs_like_git() {
+        assert_eq!(git_blob_sha(b""), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(git_blob_sha(b"target/\n"), "2f7896d1d1365eafb0da03d9fe456fac81408487");
+        let long = "node_modules/\n".repeat(20);
+        assert_eq!(git_blob_sha(long.as_bytes()), "5b8e88de0366276873eba8b38f66828bc08411a3");
+    }
+
+    #[test]
+    fn rejects_malformed_manifest() {
+        assert!(Manifest::parse("not-a-digest  Rust.gitignore").is_err());
+    }
+}
@@ -0,0 +1,1775 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+fn gi(args: &[&str]) -> Output {
+    gi_with_config(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml").as_ref(),
+        args,
+    )
+}
+
+fn gi_with_config(config: &Path, args: &[&str]) -> Output {
+    gi_command(config, args).output().unwrap()
+}
+
+fn gi_command(config: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_gi"));
+    command
+        .args(args)
+        .env("GI_FIXTURE_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"))
+        .env("GI_CONFIG", config)
+        .env("GI_DATA_DIR", std::env::temp_dir().join("gitignore-downloader-cli-data"));
+    command
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("gitignore-downloader-cli-{name}-{unique}"))
+}
+
+#[test]
+fn lists_types_from_fixtures() {
+    let output = gi(&["--list"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "community/Python/JupyterNotebooks\nGlobal/JetBrains\nGlobal/macOS\nNode\nRust\n\
+         +locks\n+macos\n"
+    );
+}
+
+#[test]
+fn lists_and_resolves_templates_in_subdirectories() {
+    let output = gi(&["--list", "--category", "global"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Global/JetBrains\nGlobal/macOS\n");
+
+    let output = gi(&["--dry-run", "jet"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("# --- Global/JetBrains ---\n"));
+}
+
+#[test]
+fn suggests_close_names_for_unknown_templates() {
+    let output = gi(&["--dry-run", "rsut"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did you mean Rust?"));
+}
+
+#[test]
+fn files_that_are_not_utf8_are_left_alone() {
+    let path = temp_path("not-utf8");
+    let original = b"caf\xe9.log\nsecret.key\n";
+    fs::write(&path, original).unwrap();
+    let path_arg = path.to_str().unwrap();
+
+    for args in [vec!["-o", path_arg, "rust"], vec!["--check", "-o", path_arg, "rust"]] {
+        let output = gi(&args);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not UTF-8"));
+    }
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn writes_templates_and_reports_missing_ones() {
+    let path = temp_path("write");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["--output", path_arg, "rust", "pyhton"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pyhton"));
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("# --- Rust ---\n# Generated by Cargo\n"));
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn check_and_diff_skip_locally_written_sections() {
+    let path = temp_path("check-local");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["rust", "--output", path_arg]).status.success());
+    let written = fs::read_to_string(&path).unwrap();
+    fs::write(&path, format!("{written}\n# --- Local ---\n/scratch\n")).unwrap();
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Local is written locally"));
+
+    let output = gi(&["diff", "local", "rust", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn names_are_capitalized_when_the_template_list_is_unavailable() {
+    let fixtures = temp_path("no-list");
+    fs::create_dir_all(fixtures.join("raw")).unwrap();
+    let rust = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/raw/Rust.gitignore");
+    fs::copy(rust, fixtures.join("raw/Rust.gitignore")).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let output = gi_command(config.as_ref(), &["--dry-run", "rust"])
+        .env("GI_FIXTURE_DIR", &fixtures)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("# --- Rust ---\n"));
+
+    let _ = fs::remove_dir_all(&fixtures);
+}
+
+#[test]
+fn searches_template_names() {
+    let output = gi(&["search", "rst"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Rust\n");
+
+    assert!(!gi(&["search", "zzz"]).status.success());
+}
+
+#[test]
+fn greps_template_contents() {
+    let output = gi(&["grep", "-i", "NODE_MODULES"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Node:7:node_modules/\n");
+}
+
+#[test]
+fn long_listing_includes_metadata() {
+    let output = gi(&["--list", "--long", "--dates"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rust = stdout.lines().find(|line| line.contains("  Rust ")).unwrap();
+    assert!(rust.contains("    137  2024-03-14"));
+    assert!(rust.ends_with("  Rust                               Generated by Cargo"), "{rust}");
+}
+
+#[test]
+fn sources_fall_back_across_hosts() {
+    let config = temp_path("sources-config");
+    fs::write(
+        &config,
+        "source = \"acme/gitignore\"\ngithub_host = \"ghe.example.com\"\n\
+         sources = [{ source = \"github/gitignore\", github_host = \"github.com\" }]\n",
+    )
+    .unwrap();
+
+    let output = gi_with_config(&config, &["--dry-run", "Internal", "Rust"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# --- Internal ---\n# Company-wide\n"), "{stdout}");
+    assert!(stdout.contains("# --- Rust ---\n# Generated by Cargo\n"), "{stdout}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Rust: not in acme/gitignore@master"), "{stderr}");
+    assert!(stderr.contains("using github/gitignore@master\n"), "{stderr}");
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn sources_fall_back_to_a_cache_bundle_last() {
+    let bundle = temp_path("sources-bundle.tar.zst");
+    let encoder = zstd::Encoder::new(fs::File::create(&bundle).unwrap(), 0).unwrap();
+    let mut archive = tar::Builder::new(encoder);
+    for (path, content) in [
+        ("templates/acme/gitignore/main/Bundled.gitignore", "*.other\n"),
+        ("templates/github/gitignore/master/Bundled.gitignore", "*.bundled\n"),
+        ("templates/github/gitignore/master/Rust.gitignore", "*.stale\n"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive.append_data(&mut header, path, content.as_bytes()).unwrap();
+    }
+    archive.into_inner().unwrap().finish().unwrap();
+    let config = temp_path("sources-bundle-config");
+    let bundle_path = bundle.to_str().unwrap();
+    fs::write(&config, format!("sources = [{{ bundle = \"{bundle_path}\" }}]\n")).unwrap();
+
+    let output = gi_with_config(&config, &["--dry-run", "Bundled", "Rust"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# --- Bundled ---\n*.bundled\n"), "{stdout}");
+    assert!(stdout.contains("# --- Rust ---\n# Generated by Cargo\n"), "{stdout}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let note = format!("Bundled: not in github/gitignore@master, using {bundle_path}");
+    assert!(stderr.contains(&note), "{stderr}");
+
+    // `--source` is that one source only.
+    let output = gi_with_config(&config, &["--dry-run", "--source", "github/gitignore", "Bundled"]);
+    assert!(!output.status.success());
+
+    let _ = fs::remove_file(&config);
+    let _ = fs::remove_file(&bundle);
+}
+
+#[test]
+fn verify_checks_downloads_against_a_given_manifest() {
+    let manifest = temp_path("checksums");
+    fs::write(
+        &manifest,
+        "905120ac0ac7cd030430bc84dacc44eceb06f8f5d66177bcfaa5776522965832  Rust.gitignore\n\
+         0000000000000000000000000000000000000000000000000000000000000000  Node.gitignore\n",
+    )
+    .unwrap();
+    let manifest_arg = manifest.to_str().unwrap();
+
+    assert!(gi(&["--dry-run", "--verify", manifest_arg, "rust"]).status.success());
+    let output = gi(&["--dry-run", "--verify", manifest_arg, "node"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+    let output = gi(&["--dry-run", "--verify", manifest_arg, "mac"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No known checksum"));
+
+    let _ = fs::remove_file(&manifest);
+}
+
+#[test]
+fn get_appends_always_templates_from_config() {
+    let config = temp_path("config");
+    fs::write(&config, "always = [\"mac\"]\n").unwrap();
+    let output = gi_with_config(&config, &["get", "--dry-run", "rust"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# --- Rust ---\n"));
+    assert!(stdout.contains("# --- Global/macOS ---\n# General\n.DS_Store\n"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn get_expands_profiles_from_config() {
+    let config = temp_path("profiles");
+    fs::write(&config, "[profiles]\nweb = [\"Node\", \"mac\"]\n").unwrap();
+    let output = gi_with_config(&config, &["get", "--dry-run", "@web"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# --- Node ---\n"));
+    assert!(stdout.contains("# --- Global/macOS ---\n"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn applies_builtin_and_configured_snippets() {
+    let config = temp_path("snippets");
+    fs::write(&config, "[snippets]\nenv = \".env\\n\"\n").unwrap();
+    let output = gi_with_config(&config, &["--dry-run", "+env", "+locks"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# --- +env ---\n.env\n"));
+    assert!(stdout.contains("# --- +locks ---\n"));
+
+    let output = gi_with_config(&config, &["--dry-run", "+nope"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown snippet +nope"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn flattens_snippet_includes() {
+    let config = temp_path("includes");
+    fs::write(
+        &config,
+        r#"
+[snippets.web]
+include = ["Node", "snippet:locks"]
+content = "/local-only\n"
+
+[snippets.loop]
+include = ["+loop"]
+"#,
+    )
+    .unwrap();
+    let output = gi_with_config(&config, &["--dry-run", "+web"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let node = stdout.find("node_modules/").unwrap();
+    let locks = stdout.find("yarn.lock").unwrap();
+    let local = stdout.find("/local-only\n").unwrap();
+    assert!(node < locks && locks < local);
+
+    let output = gi_with_config(&config, &["--dry-run", "+loop"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Snippet include cycle: +loop -> +loop"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn lists_and_searches_as_json() {
+    let output = gi(&["--list", "--json"]);
+    assert!(output.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries[1]["name"], "Global/JetBrains");
+    assert_eq!(entries[1]["category"], "global");
+    assert_eq!(entries[3]["name"], "Node");
+    assert_eq!(entries[3]["category"], "root");
+    assert_eq!(entries[3]["source"], "github/gitignore@master");
+    assert_eq!(entries[3]["cached"], false);
+    assert_eq!(entries[5]["name"], "+locks");
+    assert_eq!(entries[5]["category"], "snippet");
+
+    let output = gi(&["search", "rst", "--json"]);
+    assert!(output.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    assert_eq!(entries[0]["name"], "Rust");
+}
+
+#[test]
+fn dry_run_json_describes_each_template() {
+    let output = gi(&["--dry-run", "--json", "rust", "+locks"]);
+    assert!(output.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries[0]["name"], "Rust");
+    assert_eq!(
+        entries[0]["url"],
+        "https://raw.githubusercontent.com/github/gitignore/master/Rust.gitignore"
+    );
+    assert_eq!(entries[0]["reference"], "master");
+    let content = entries[0]["content"].as_str().unwrap();
+    assert!(content.contains("target/"));
+    assert_eq!(entries[0]["bytes"], content.len());
+    assert_eq!(entries[1]["name"], "+locks");
+    assert!(entries[1]["url"].is_null());
+}
+
+#[test]
+fn long_listing_looks_up_dates_only_when_asked() {
+    let output = gi(&["--list", "--long"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rust = stdout.lines().find(|line| line.contains("  Rust ")).unwrap();
+    assert!(rust.contains("    137  -  "), "{rust}");
+}
+
+#[test]
+fn exports_catalog_as_tsv() {
+    let output = gi(&["--list", "--format", "tsv", "--dates"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "name\tcategory\tsize\tlast_updated\n\
+         community/Python/JupyterNotebooks\tcommunity\t85\t2023-06-30\n\
+         Global/JetBrains\tglobal\t106\t2024-01-22\n\
+         Global/macOS\tglobal\t45\t2023-11-02\n\
+         Node\troot\t115\t2024-05-14\n\
+         Rust\troot\t137\t2024-03-14\n"
+    );
+}
+
+#[test]
+fn colors_follow_clicolor_force_and_no_color() {
+    let show = |vars: &[(&str, &str)]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_gi"));
+        command
+            .args(["show", "rust"])
+            .env("GI_FIXTURE_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"))
+            .env("GI_CONFIG", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml"))
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE");
+        for (key, value) in vars {
+            command.env(key, value);
+        }
+        String::from_utf8(command.output().unwrap().stdout).unwrap()
+    };
+
+    assert!(!show(&[]).contains('\x1b'));
+    assert!(show(&[("CLICOLOR_FORCE", "1")]).contains('\x1b'));
+    assert!(!show(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]).contains('\x1b'));
+}
+
+#[test]
+fn quiet_and_verbose_control_chatter() {
+    let path = temp_path("quiet");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["-q", "--output", path_arg, "rust"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(fs::read_to_string(&path).unwrap().contains("# --- Rust ---"));
+
+    let output = gi(&["-q", "--output", path_arg, "rust"]);
+    assert!(output.stderr.is_empty());
+
+    let output = gi(&["-v", "--dry-run", "rust"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("raw/Rust.gitignore"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn writes_logs_to_a_file() {
+    let log = temp_path("log");
+    let output = gi(&["--dry-run", "--log-file", log.to_str().unwrap(), "rust"]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+
+    let logged = fs::read_to_string(&log).unwrap();
+    assert!(logged.contains("reading fixture"));
+    assert!(logged.contains("raw/Rust.gitignore"));
+
+    let _ = fs::remove_file(&log);
+}
+
+#[test]
+fn yes_skips_detect_confirmation() {
+    let dir = temp_path("yes");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+    let output_path = dir.join(".gitignore");
+    let dir_arg = dir.to_str().unwrap();
+    let output_arg = output_path.to_str().unwrap();
+
+    let output = gi(&["detect", dir_arg, "--apply", "--output", output_arg]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass --yes"));
+    assert!(!output_path.exists());
+
+    let output = gi(&["detect", dir_arg, "--apply", "--yes", "--output", output_arg]);
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&output_path).unwrap().contains("# --- Rust ---"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn diff_previews_pending_changes() {
+    let path = temp_path("diff");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "secret.txt\n").unwrap();
+
+    let output = gi(&["--diff", "--dry-run", "--output", path_arg, "rust"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" secret.txt\n"));
+    assert!(stdout.contains("+# --- Rust ---\n"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "secret.txt\n");
+
+    let output = gi(&["--diff", "--output", path_arg, "rust"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("+target/\n"));
+    assert!(fs::read_to_string(&path).unwrap().contains("# --- Rust ---"));
+
+    let output = gi(&["--diff", "--overwrite", "--dry-run", "--output", path_arg, "node"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("-secret.txt\n"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn backs_up_before_changing_a_file() {
+    let path = temp_path("backup");
+    let path_arg = path.to_str().unwrap();
+    let backup = PathBuf::from(format!("{path_arg}.orig"));
+    fs::write(&path, "secret.txt\n").unwrap();
+
+    let output = gi(&["--backup=.orig", "--overwrite", "--output", path_arg, "rust"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "secret.txt\n");
+    assert!(fs::read_to_string(&path).unwrap().starts_with("# --- Rust ---"));
+    let _ = fs::remove_file(&backup);
+
+    let output = gi(&["--no-backup", "--output", path_arg, "node"]);
+    assert!(output.status.success());
+    assert!(!backup.exists());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn undo_reverts_the_last_write() {
+    let path = temp_path("undo");
+    let path_arg = path.to_str().unwrap();
+    let data_dir = temp_path("undo-data");
+    let data_arg = data_dir.to_str().unwrap();
+
+    assert!(gi(&["--data-dir", data_arg, "--output", path_arg, "rust"]).status.success());
+    let rust_only = fs::read_to_string(&path).unwrap();
+    assert!(gi(&["--data-dir", data_arg, "--output", path_arg, "node"]).status.success());
+    assert!(fs::read_to_string(&path).unwrap().contains("# --- Node ---"));
+
+    let output = gi(&["undo", "--data-dir", data_arg]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("undid Node"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), rust_only);
+
+    assert!(gi(&["undo", "--data-dir", data_arg]).status.success());
+    assert!(!path.exists());
+
+    let output = gi(&["undo", "--data-dir", data_arg]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Nothing to undo"));
+
+    let _ = fs::remove_dir_all(&data_dir);
+}
+
+#[test]
+fn output_dash_writes_to_stdout() {
+    let output = gi(&["--output", "-", "rust", "node"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# --- Rust ---"));
+    assert!(stdout.contains("\n# --- Node ---"));
+    assert!(!Path::new("-").exists());
+}
+
+#[test]
+fn writes_to_every_output() {
+    let file = temp_path("multi-file");
+    let dir = temp_path("multi-dir");
+    fs::create_dir_all(&dir).unwrap();
+
+    let output = gi(&[
+        "--output",
+        file.to_str().unwrap(),
+        "--output",
+        dir.to_str().unwrap(),
+        "rust",
+    ]);
+    assert!(output.status.success());
+    let written = fs::read_to_string(&file).unwrap();
+    assert!(written.starts_with("# --- Rust ---"));
+    assert_eq!(fs::read_to_string(dir.join(".gitignore")).unwrap(), written);
+
+    let output = gi(&["--check", "-o", file.to_str().unwrap(), "-o", "other", "rust"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("single --output"));
+
+    let _ = fs::remove_file(&file);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn parents_creates_missing_directories() {
+    let root = temp_path("parents");
+    let path = root.join("sub/dir/.gitignore");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["--output", path_arg, "rust"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--parents"));
+
+    assert!(gi(&["--parents", "--output", path_arg, "rust"]).status.success());
+    assert!(fs::read_to_string(&path).unwrap().starts_with("# --- Rust ---"));
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn target_global_writes_the_excludes_file() {
+    let home = temp_path("global-home");
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |args: &[&str]| {
+        gi_command(config.as_ref(), args)
+            .env("HOME", &home)
+            .env_remove("XDG_CONFIG_HOME")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .output()
+            .unwrap()
+    };
+
+    assert!(run(&["--target", "global", "+macos"]).status.success());
+    let default = home.join(".config/git/ignore");
+    assert!(fs::read_to_string(&default).unwrap().contains(".DS_Store"));
+
+    fs::write(home.join(".gitconfig"), "[core]\n\texcludesFile = ~/.gitignore_global\n").unwrap();
+    assert!(run(&["--target", "global", "+macos"]).status.success());
+    assert!(fs::read_to_string(home.join(".gitignore_global")).unwrap().contains(".DS_Store"));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn repo_root_writes_at_the_top_of_the_repository() {
+    let root = temp_path("repo-root");
+    let nested = root.join("src/deep");
+    fs::create_dir_all(&nested).unwrap();
+    let init = Command::new("git").arg("init").arg("-q").arg(&root).status();
+    if !init.is_ok_and(|status| status.success()) {
+        fs::create_dir(root.join(".git")).unwrap();
+    }
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let output = gi_command(config.as_ref(), &["--repo-root", "rust"])
+        .current_dir(&nested)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(fs::read_to_string(root.join(".gitignore")).unwrap().starts_with("# --- Rust ---"));
+    assert!(!nested.join(".gitignore").exists());
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn dedupe_lines_drops_repeated_patterns() {
+    let config = temp_path("dedupe");
+    fs::write(&config, "[snippets]\nlogs = \"*.log\\n/tmp\\n\"\n").unwrap();
+    let output = gi_with_config(&config, &["--dry-run", "--dedupe-lines", "node", "+logs"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# --- Node + +logs ---\n# Written with: --dedupe-lines\n"));
+    assert!(stdout.ends_with("\n\n# From +logs\n# *.log (see Node)\n/tmp\n\n"), "{stdout}");
+    assert_eq!(stdout.matches("# --- ").count(), 1);
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn merged_sections_pass_check() {
+    let path = temp_path("dedupe-check");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["--dedupe-lines", "rust", "node", "--output", path_arg]).status.success());
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Rust + Node is up to date"));
+    assert!(gi(&["--check", "node", "--output", path_arg]).status.success());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn sort_orders_patterns_within_groups() {
+    let output = gi(&["--dry-run", "--sort", "rust"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# will have compiled files and executables\ndebug/\ntarget/\n"));
+}
+
+#[test]
+fn sorted_sections_pass_check() {
+    let path = temp_path("sort-check");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["--sort", "rust", "--output", path_arg]).status.success());
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("# --- Rust ---\n# Written with: --sort\n"), "{written}");
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn minify_strips_comments_and_blank_lines() {
+    let output = gi(&["--dry-run", "--minify", "rust"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        "# --- Rust ---\n# Written with: --minify\ndebug/\ntarget/\n**/*.rs.bk\n\n"
+    );
+}
+
+#[test]
+fn minified_sections_pass_check() {
+    let path = temp_path("minify-check");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["--minify", "rust", "--output", path_arg]).status.success());
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = gi(&["diff", "rust", "--output", path_arg]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Rust is up to date"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn normalize_tidies_an_existing_file() {
+    let path = temp_path("normalize");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "### Rust ###\ntarget/   \n\n\n*.log\ntarget/\n").unwrap();
+
+    let output = gi(&["normalize", "--output", path_arg]);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# --- Rust ---\ntarget/\n\n*.log\n");
+
+    let output = gi(&["normalize", "--output", path_arg]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("already tidy"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn lint_reports_problems_and_honors_config() {
+    let path = temp_path("lint");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "build/\nbuild/output/\n*.log\n*.log\n").unwrap();
+
+    let output = gi(&["lint", "--output", path_arg]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(":2: shadowed: `build/output/` is already ignored by `build/`"));
+    assert!(stdout.contains(":4: duplicate: `*.log` already appears on line 3"));
+
+    let config = temp_path("lint-config");
+    fs::write(&config, "[lint]\ndisable = [\"shadowed\", \"duplicate\"]\n").unwrap();
+    assert!(gi_with_config(&config, &["lint", "--output", path_arg]).status.success());
+
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn lint_fix_applies_safe_fixes() {
+    let path = temp_path("lint-fix");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "build/\nbuild/output/\n*.log  \n*.log\n").unwrap();
+
+    let output = gi(&["lint", "--fix", "--output", path_arg]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("-build/output/\n"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "build/\n*.log\n");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn warns_about_negation_conflicts() {
+    let config = temp_path("negation");
+    fs::write(&config, "[snippets]\nkeep = \"!npm-debug.log\\n\"\n").unwrap();
+
+    let output = gi_with_config(&config, &["--dry-run", "+keep", "node"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("`!npm-debug.log` in +keep re-includes what `*.log` in Node ignores"));
+    assert!(stderr.contains("apply in this order: Node, +keep"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn diff_reports_shadowed_patterns() {
+    let config = temp_path("shadowed");
+    fs::write(&config, "[snippets]\ncache = \"node_modules/.cache/\\n\"\n").unwrap();
+    let path = temp_path("shadowed-output");
+
+    let output = gi_with_config(
+        &config,
+        &["--diff", "--dry-run", "--output", path.to_str().unwrap(), "node", "+cache"],
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("`node_modules/.cache/` is already ignored by `node_modules/`"));
+
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn explain_attributes_lines_to_templates() {
+    let path = temp_path("explain");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["--output", path_arg, "rust"]).status.success());
+    let mut content = fs::read_to_string(&path).unwrap();
+    content.push_str("/local\n");
+    fs::write(&path, content).unwrap();
+
+    let output = gi(&["explain", path_arg]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Rust       target/\n"));
+    assert!(stdout.contains("unmanaged  /local\n"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_reports_the_deciding_rule() {
+    let dir = temp_path("test-cmd");
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join(".gitignore");
+    fs::write(&output, "*.log\n!keep.log\n").unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let result = gi_command(
+        config.as_ref(),
+        &["test", "debug.log", "keep.log", "target/debug/gi", "src/main.rs", "--with", "rust"],
+    )
+    .current_dir(&dir)
+    .output()
+    .unwrap();
+    assert!(result.status.success());
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("debug.log: ignored by `*.log` (line 1)\n"));
+    assert!(stdout.contains("keep.log: re-included by `!keep.log` (line 2)\n"));
+    assert!(stdout.contains("target/debug/gi: ignored by `target/`"));
+    assert!(stdout.contains("src/main.rs: not ignored\n"));
+    assert_eq!(fs::read_to_string(&output).unwrap(), "*.log\n!keep.log\n");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn suggest_ranks_templates_by_untracked_files() {
+    let dir = temp_path("suggest");
+    fs::create_dir_all(dir.join("node_modules/left-pad")).unwrap();
+    fs::create_dir_all(dir.join("target/debug")).unwrap();
+    fs::write(dir.join("node_modules/left-pad/index.js"), "").unwrap();
+    fs::write(dir.join("npm-debug.log"), "").unwrap();
+    fs::write(dir.join("target/debug/app"), "").unwrap();
+    fs::write(dir.join("README.md"), "").unwrap();
+    let init = Command::new("git").args(["init", "-q"]).current_dir(&dir).status();
+    if !init.is_ok_and(|status| status.success()) {
+        return;
+    }
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let output = gi_command(config.as_ref(), &["suggest"]).current_dir(&dir).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Node: 2 untracked (node_modules/, npm-debug.log)\nRust: 1 untracked (target/)\n\
+         Not covered by any template:\n  *.md (README.md)\n"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn suggest_skips_templates_it_cannot_read() {
+    let dir = temp_path("suggest-skip");
+    fs::create_dir_all(dir.join("node_modules")).unwrap();
+    fs::create_dir_all(dir.join("target")).unwrap();
+    fs::write(dir.join("node_modules/index.js"), "").unwrap();
+    fs::write(dir.join("target/app"), "").unwrap();
+    let init = Command::new("git").args(["init", "-q"]).current_dir(&dir).status();
+    if !init.is_ok_and(|status| status.success()) {
+        return;
+    }
+    // The catalog lists Rust, but there is no file to read it from.
+    let fixtures = temp_path("suggest-skip-fixtures");
+    let real = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    fs::create_dir_all(fixtures.join("raw")).unwrap();
+    fs::copy(real.join("contents.json"), fixtures.join("contents.json")).unwrap();
+    fs::copy(real.join("raw/Node.gitignore"), fixtures.join("raw/Node.gitignore")).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let output = gi_command(config.as_ref(), &["suggest"])
+        .env("GI_FIXTURE_DIR", &fixtures)
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.starts_with("Node: 1 untracked (node_modules/)\n"), "{stdout}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Skipping Rust"));
+
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&fixtures);
+}
+
+#[test]
+fn check_ignore_confirms_sample_artifacts() {
+    let dir = temp_path("check-ignore");
+    fs::create_dir_all(&dir).unwrap();
+    let init = Command::new("git").args(["init", "-q"]).current_dir(&dir).status();
+    if !init.is_ok_and(|status| status.success()) {
+        return;
+    }
+    let output = dir.join(".gitignore");
+    let output_arg = output.to_str().unwrap();
+
+    let result = gi(&["--check-ignore", "--output", output_arg, "rust", "node"]);
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stdout).contains("git ignores all 3 sample"));
+
+    let config = temp_path("check-ignore-config");
+    fs::write(&config, "[rules.Node]\nremove = [\"*.log\", \"npm-debug.log*\"]\n").unwrap();
+    let args = ["--check-ignore", "--overwrite", "-o", output_arg, "node"];
+    let result = gi_with_config(&config, &args);
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("npm-debug.log (Node)"));
+
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn check_ignore_reads_only_the_written_file() {
+    if Command::new("git").arg("--version").output().is_err() {
+        return;
+    }
+    let dir = temp_path("check-ignore-other");
+    fs::create_dir_all(&dir).unwrap();
+    // Would hide every miss if git consulted the files around the output.
+    fs::write(dir.join(".gitignore"), "*\n").unwrap();
+    let output = dir.join("patterns.txt");
+    let output_arg = output.to_str().unwrap();
+
+    let result = gi(&["--check-ignore", "--output", output_arg, "rust"]);
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(stdout.contains("git ignores all 1 sample"), "{stdout}");
+
+    let config = temp_path("check-ignore-other-config");
+    fs::write(&config, "[rules.Node]\nremove = [\"*.log\", \"npm-debug.log*\"]\n").unwrap();
+    let args = ["--check-ignore", "--overwrite", "-o", output_arg, "node"];
+    let result = gi_with_config(&config, &args);
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("npm-debug.log (Node)"));
+
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_file(&config);
+}
+
+#[test]
+fn hook_install_writes_a_check_hook() {
+    let dir = temp_path("hook");
+    fs::create_dir_all(&dir).unwrap();
+    let init = Command::new("git").args(["init", "-q"]).current_dir(&dir).status();
+    if !init.is_ok_and(|status| status.success()) {
+        return;
+    }
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |args: &[&str]| {
+        gi_command(config.as_ref(), args).current_dir(&dir).output().unwrap()
+    };
+
+    assert!(run(&["hook", "install"]).status.success());
+    let hook = fs::read_to_string(dir.join(".git/hooks/pre-commit")).unwrap();
+    assert!(hook.contains("--check --output '.gitignore'"));
+    assert!(run(&["hook", "install"]).status.success());
+
+    fs::write(dir.join(".git/hooks/pre-push"), "#!/bin/sh\necho mine\n").unwrap();
+    let output = run(&["hook", "install", "--kind", "pre-push"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    assert!(run(&["hook", "install", "--kind", "pre-push", "--force"]).status.success());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn install_git_template_seeds_new_repositories() {
+    let home = temp_path("template-home");
+    fs::create_dir_all(&home).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let output = gi_command(config.as_ref(), &["install-git-template", "rust"])
+        .env("HOME", &home)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .output()
+        .unwrap();
+    if !home.join(".gitconfig").exists() {
+        // No git to record init.templateDir with.
+        assert!(!output.status.success());
+        return;
+    }
+    assert!(output.status.success());
+
+    let repo = home.join("fresh");
+    let init = Command::new("git")
+        .args(["init", "-q"])
+        .arg(&repo)
+        .env("HOME", &home)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .status()
+        .unwrap();
+    assert!(init.success());
+    let exclude = fs::read_to_string(repo.join(".git/info/exclude")).unwrap();
+    assert!(exclude.starts_with("# --- Rust ---\n"));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn init_writes_the_detected_templates() {
+    let dir = temp_path("init");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+    let init = || gi_command(config.as_ref(), &["init", "--yes"]).current_dir(&dir).output();
+    let output = init().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Detected Rust (found Cargo.toml)"));
+    let written = fs::read_to_string(dir.join(".gitignore")).unwrap();
+    assert!(written.starts_with("# --- Rust ---\n"));
+
+    let again = init().unwrap();
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already exists"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cache_reports_and_clears_entries() {
+    let home = temp_path("cache-home");
+    let dir = home.join("gitignore-downloader");
+    fs::create_dir_all(dir.join("templates")).unwrap();
+    fs::write(dir.join("types.json"), "{}").unwrap();
+    fs::write(dir.join("templates/Rust.gitignore"), "target/\n").unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let cache = |args: &[&str]| {
+        let mut full = vec!["cache"];
+        full.extend(args);
+        gi_command(config.as_ref(), &full).env("XDG_CACHE_HOME", &home).output().unwrap()
+    };
+
+    let path = cache(&["path"]);
+    assert_eq!(String::from_utf8_lossy(&path.stdout).trim(), dir.to_string_lossy());
+    let info = String::from_utf8_lossy(&cache(&["info"]).stdout).into_owned();
+    assert!(info.contains("types.json\t2 B\t"), "{info}");
+    assert!(info.contains("2 file(s), 10 B"), "{info}");
+
+    let purge = cache(&["purge", "--older-than", "1d"]);
+    assert!(String::from_utf8_lossy(&purge.stdout).contains("Purged 0 file(s) older than 1d"));
+    assert!(cache(&["clear", "--types"]).status.success());
+    assert!(!dir.join("types.json").exists());
+    assert!(dir.join("templates/Rust.gitignore").exists());
+    assert!(cache(&["clear"]).status.success());
+    assert!(!dir.join("templates").exists());
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn locked_runs_only_read_the_cache() {
+    let home = temp_path("locked-home");
+    let templates = home.join("gitignore-downloader/templates/github/gitignore/master");
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(templates.join("Rust.gitignore"), "/target\n").unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    // No fixtures: anything not served from the cache would go to the network.
+    let locked = |args: &[&str]| {
+        let mut command = gi_command(config.as_ref(), args);
+        command.env_remove("GI_FIXTURE_DIR").env("XDG_CACHE_HOME", &home).output().unwrap()
+    };
+
+    let output = locked(&["--locked", "--output", "-", "rust"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("# --- Rust ---\n/target\n"));
+
+    let output = locked(&["--locked", "--output", "-", "go"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Go is not in the cache (--locked doesn't download)"), "{stderr}");
+
+    let output = locked(&["--locked", "--list"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No cached template list for github/gitignore"), "{stderr}");
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn scripts_without_types_get_suggestions_instead_of_writes() {
+    let dir = temp_path("no-types");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |args: &[&str]| {
+        let mut command = gi_command(config.as_ref(), args);
+        command.current_dir(&dir).stdin(Stdio::null()).output().unwrap()
+    };
+
+    let output = run(&[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{stderr}");
+    assert!(stderr.contains("suggests Rust"), "{stderr}");
+    assert!(!dir.join(".gitignore").exists());
+
+    let output = run(&["--yes"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(fs::read_to_string(dir.join(".gitignore")).unwrap().contains("target"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn self_update_check_only_reports_the_release() {
+    let binary = fs::read(env!("CARGO_BIN_EXE_gi")).unwrap();
+    let output = gi(&["self-update", "--check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("gi 99.0.0 is available"), "{stdout}");
+    assert_eq!(fs::read(env!("CARGO_BIN_EXE_gi")).unwrap(), binary);
+}
+
+#[test]
+fn self_update_installs_a_release_only_if_its_checksum_matches() {
+    use std::env::consts::{ARCH, EXE_SUFFIX, OS};
+
+    let dir = temp_path("self-update");
+    let assets = dir.join("assets");
+    fs::create_dir_all(&assets).unwrap();
+    let artifact = format!("gi-{ARCH}-{OS}{EXE_SUFFIX}");
+    let asset = |name: String| {
+        let url = format!("https://example.com/releases/{name}");
+        serde_json::json!({ "name": name, "browser_download_url": url })
+    };
+    let release = serde_json::json!({
+        "tag_name": "v99.0.0",
+        "assets": [asset(artifact.clone()), asset(format!("{artifact}.sha256"))],
+    });
+    fs::write(dir.join("release.json"), release.to_string()).unwrap();
+    let build = b"the 99.0.0 build\n";
+    fs::write(assets.join(&artifact), build).unwrap();
+
+    // A copy stands in for the installed binary, since that is what gets replaced.
+    let installed = dir.join(format!("gi{EXE_SUFFIX}"));
+    let original = fs::read(env!("CARGO_BIN_EXE_gi")).unwrap();
+    fs::copy(env!("CARGO_BIN_EXE_gi"), &installed).unwrap();
+    let update = |digest: &str| {
+        let checksums = format!("{digest}  {artifact}\n");
+        fs::write(assets.join(format!("{artifact}.sha256")), checksums).unwrap();
+        Command::new(&installed)
+            .arg("self-update")
+            .env("GI_FIXTURE_DIR", &dir)
+            .env("GI_CONFIG", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml"))
+            .env("GI_DATA_DIR", dir.join("data"))
+            .output()
+            .unwrap()
+    };
+
+    let output = update(&"0".repeat(64));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+    assert_eq!(fs::read(&installed).unwrap(), original);
+
+    let digest: String = Sha256::digest(build).iter().map(|b| format!("{b:02x}")).collect();
+    let output = update(&digest);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("-> 99.0.0"));
+    assert_eq!(fs::read(&installed).unwrap(), build);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&installed).unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+    // Replaced in one rename: no temporary copy is left next to it.
+    let mut left: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    left.sort();
+    assert_eq!(left, ["assets", format!("gi{EXE_SUFFIX}").as_str(), "release.json"]);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn doctor_reports_problems_with_hints() {
+    let home = temp_path("doctor-home");
+    fs::create_dir_all(home.join("gitignore-downloader")).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let doctor = |output: &Path| {
+        let output = output.to_str().unwrap();
+        gi_command(config.as_ref(), &["doctor", "--output", output])
+            .env("XDG_CACHE_HOME", &home)
+            .env_remove("SSL_CERT_FILE")
+            .env_remove("SSL_CERT_DIR")
+            .output()
+            .unwrap()
+    };
+
+    let healthy = doctor(&home.join(".gitignore"));
+    let stdout = String::from_utf8_lossy(&healthy.stdout).into_owned();
+    assert!(healthy.status.success(), "{stdout}");
+    assert!(stdout.contains("api: fixture mode"), "{stdout}");
+    assert!(stdout.contains("cache: 0 file(s)"), "{stdout}");
+
+    fs::write(home.join("gitignore-downloader/types.json"), "{\"truncated").unwrap();
+    let broken = doctor(&home.join("missing/.gitignore"));
+    let stdout = String::from_utf8_lossy(&broken.stdout).into_owned();
+    assert!(!broken.status.success());
+    assert!(stdout.contains("hint: run `gi cache clear --types`"), "{stdout}");
+    assert!(stdout.contains("hint: create it, or pass --parents"), "{stdout}");
+    assert!(String::from_utf8_lossy(&broken.stderr).contains("2 problem(s) found"));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn overrides_replace_upstream_templates() {
+    let dir = temp_path("overrides");
+    fs::create_dir_all(dir.join("overrides")).unwrap();
+    let config = dir.join("config.toml");
+    fs::write(&config, "").unwrap();
+    fs::write(dir.join("overrides/Rust.gitignore"), "target/\n/local-only\n").unwrap();
+
+    let output = gi_with_config(&config, &["rust", "node", "--output", "-"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# --- Rust ---\ntarget/\n/local-only\n"), "{stdout}");
+    assert!(stdout.contains("node_modules/"), "{stdout}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn post_write_commands_see_the_output_and_templates() {
+    let dir = temp_path("post-write");
+    fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    let marker = dir.join("ran");
+    let command = format!("echo \"$GI_OUTPUT $GI_TEMPLATES\" > '{}'", marker.display());
+    fs::write(&config, format!("post_write = ['''{command}''']\n")).unwrap();
+    let output_path = dir.join(".gitignore");
+    let output_arg = output_path.to_str().unwrap();
+
+    let output = gi_with_config(&config, &["rust", "node", "--output", output_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let ran = fs::read_to_string(&marker).unwrap();
+    assert_eq!(ran, format!("{} Rust,Node\n", output_path.display()));
+
+    fs::write(&config, "post_write = ['exit 3']\n").unwrap();
+    let failed = gi_with_config(&config, &["rust", "--overwrite", "--output", output_arg]);
+    assert!(!failed.status.success());
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("`exit 3` failed"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn writes_stay_undoable_when_a_post_write_command_fails() {
+    let dir = temp_path("post-write-undo");
+    fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    fs::write(&config, "post_write = ['exit 3']\n").unwrap();
+    let output_path = dir.join(".gitignore");
+    let output_arg = output_path.to_str().unwrap();
+    let data_arg = dir.join("data");
+    let data_arg = data_arg.to_str().unwrap();
+
+    let failed = gi_with_config(&config, &["rust", "--data-dir", data_arg, "-o", output_arg]);
+    assert!(!failed.status.success());
+    assert!(output_path.exists());
+
+    let output = gi_with_config(&config, &["undo", "--data-dir", data_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!output_path.exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn provenance_records_where_sections_came_from() {
+    let path = temp_path("provenance");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["rust", "--provenance", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    let source = "https://raw.githubusercontent.com/github/gitignore/master/Rust.gitignore";
+    assert!(written.starts_with(&format!("# --- Rust ---\n# Source: {source}\n# Fetched: ")));
+    assert!(written.contains("Z\n# Generated by Cargo\n"), "{written}");
+
+    // The fetch time doesn't make the section look stale.
+    assert!(gi(&["--check", "rust", "--output", path_arg]).status.success());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn anchors_insert_sections_mid_file() {
+    let path = temp_path("anchor");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "*.js\n!keep.js\n").unwrap();
+
+    let output = gi(&["node", "--before", "!keep.js", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("*.js\n\n# --- Node ---\n"), "{written}");
+    assert!(written.ends_with("\n\n!keep.js\n"), "{written}");
+
+    let output = gi(&["rust", "--after", "missing", "--output", path_arg]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No section or line `missing`"));
+
+    let output = gi(&["rust", "--at-top", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(fs::read_to_string(&path).unwrap().starts_with("# --- Rust ---\n"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn sections_inserted_mid_file_leave_the_lines_below_alone() {
+    let path = temp_path("anchor-end");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "*.js\n!keep.js\n").unwrap();
+
+    assert!(gi(&["node", "--before", "!keep.js", "--output", path_arg]).status.success());
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.ends_with("\n# --- end Node ---\n\n!keep.js\n"), "{written}");
+
+    let output = gi(&["--check", "node", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = gi(&["remove", "node", "--yes", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "*.js\n\n!keep.js\n");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn reorder_sorts_sections_and_keeps_hand_written_lines() {
+    let path = temp_path("reorder");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "# mine\n.env\n\n# --- Rust ---\ntarget/\n\n# --- Node ---\nnode_modules/\n")
+        .unwrap();
+
+    let output = gi(&["reorder", "--order", "alphabetical", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let expected = "# mine\n.env\n\n# --- Node ---\nnode_modules/\n\n# --- Rust ---\ntarget/\n\n";
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+
+    let output = gi(&["reorder", "rust", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let sections: Vec<String> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("# ---"))
+        .map(str::to_string)
+        .collect();
+    assert_eq!(sections, ["# --- Rust ---", "# --- Node ---"]);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn from_file_reads_the_template_list() {
+    let list = temp_path("from-file-list");
+    fs::write(&list, "# our stack\nrust\n\nnode  # frontend\n").unwrap();
+    let path = temp_path("from-file");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["--from-file", list.to_str().unwrap(), "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.contains("# --- Rust ---\n"), "{written}");
+    assert!(written.contains("# --- Node ---\n"), "{written}");
+
+    let output = gi(&["--from-file", "missing-list.txt", "--output", path_arg]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot read missing-list.txt"));
+
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&list);
+}
+
+#[test]
+fn sync_makes_the_file_match_the_manifest() {
+    let dir = temp_path("sync");
+    fs::create_dir_all(&dir).unwrap();
+    let manifest = dir.join(".gitignore.toml");
+    fs::write(&manifest, "templates = [\"node\", \"+env\"]\n\n[snippets]\nenv = \".env\\n\"\n")
+        .unwrap();
+    let output = dir.join(".gitignore");
+    fs::write(&output, "# ours\n\n# --- Rust ---\ntarget/\n\n# --- +env ---\nold\n\n").unwrap();
+
+    let run = gi(&["sync", "--manifest", manifest.to_str().unwrap()]);
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert!(String::from_utf8_lossy(&run.stderr).contains("Dropping Rust"));
+    let written = fs::read_to_string(&output).unwrap();
+    assert!(written.starts_with("# ours\n\n# --- Node ---\n"), "{written}");
+    assert!(written.ends_with("# --- +env ---\n.env\n\n"), "{written}");
+    assert!(!written.contains("target/"), "{written}");
+
+    let run = gi(&["sync", "--manifest", manifest.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&run.stdout).contains("already in sync"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sync_keeps_lines_added_below_the_sections() {
+    let dir = temp_path("sync-appended");
+    fs::create_dir_all(&dir).unwrap();
+    let manifest = dir.join(".gitignore.toml");
+    fs::write(&manifest, "templates = [\"Rust\"]\n").unwrap();
+    let output = dir.join(".gitignore");
+    let output_arg = output.to_str().unwrap();
+    let sync = || gi(&["sync", "--manifest", manifest.to_str().unwrap()]);
+
+    assert!(gi(&["rust", "--output", output_arg]).status.success());
+    let written = fs::read_to_string(&output).unwrap();
+    fs::write(&output, format!("{written}.env\n")).unwrap();
+    let run = sync();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert!(String::from_utf8_lossy(&run.stdout).contains("already in sync"));
+    let synced = fs::read_to_string(&output).unwrap();
+    assert_eq!(synced, format!("{written}.env\n"));
+
+    // Written before end lines: Rust ends at the last line of its template.
+    let rust = fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/raw/Rust.gitignore"
+    ))
+    .unwrap();
+    fs::write(&output, format!("# --- Rust ---\n{rust}\n.env\n")).unwrap();
+    let run = sync();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    let synced = fs::read_to_string(&output).unwrap();
+    assert!(synced.ends_with("# --- end Rust ---\n\n.env\n"), "{synced}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sync_keeps_sections_written_from_local_patterns() {
+    let dir = temp_path("sync-local");
+    fs::create_dir_all(&dir).unwrap();
+    let manifest = dir.join(".gitignore.toml");
+    fs::write(&manifest, "templates = [\"node\"]\n").unwrap();
+    let output = dir.join(".gitignore");
+    fs::write(
+        &output,
+        "# --- Local ---\n/scratch\n\n# --- Imported ---\n*.orig\n\n# --- Rust ---\ntarget/\n",
+    )
+    .unwrap();
+
+    let run = gi(&["sync", "--manifest", manifest.to_str().unwrap()]);
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    let written = fs::read_to_string(&output).unwrap();
+    assert!(written.starts_with("# --- Node ---\n"), "{written}");
+    assert!(written.contains("# --- Local ---\n/scratch\n"), "{written}");
+    assert!(written.contains("# --- Imported ---\n*.orig\n"), "{written}");
+    assert!(!written.contains("target/"), "{written}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn stdin_template_is_written_like_a_fetched_one() {
+    let path = temp_path("stdin");
+    let path_arg = path.to_str().unwrap();
+    let pipe = |args: &[&str], input: &str| {
+        let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+        let mut child = gi_command(config.as_ref(), args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = pipe(&["rust", "-", "--output", path_arg], "/scratch/\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.ends_with("\n# --- Stdin ---\n/scratch/\n# --- end Stdin ---\n"), "{written}");
+
+    // Already there under that name, so skipped like any other template.
+    let output = pipe(&["--stdin", "stdin", "--output", path_arg], "/other/\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Skipping stdin"));
+
+    let output = pipe(&["--stdin", "local", "--output", path_arg], "");
+    assert!(!output.status.success());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn clipboard_replaces_writing_unless_an_output_is_given() {
+    let dir = temp_path("clipboard");
+    fs::create_dir_all(&dir).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |args: &[&str]| {
+        gi_command(config.as_ref(), args)
+            .current_dir(&dir)
+            .env_remove("DISPLAY")
+            .env_remove("WAYLAND_DISPLAY")
+            .output()
+            .unwrap()
+    };
+
+    // No display to copy to here, so it fails, but it never meant to write the file.
+    let output = run(&["rust", "--clipboard"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("clipboard"));
+    assert!(!dir.join(".gitignore").exists());
+
+    let output = run(&["rust", "--clipboard", "--output", ".gitignore"]);
+    assert!(!output.status.success());
+    assert!(dir.join(".gitignore").exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn edit_writes_what_the_editor_saved() {
+    let path = temp_path("edit");
+    let path_arg = path.to_str().unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |editor: &str| {
+        gi_command(config.as_ref(), &["rust", "--edit", "--output", path_arg])
+            .env("VISUAL", editor)
+            .output()
+            .unwrap()
+    };
+
+    let output = run("false");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("left"));
+    assert!(!path.exists());
+
+    let output = run("sed -i.orig s/target/edited/");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.contains("\nedited/\n"), "{written}");
+    assert!(!written.contains("target"), "{written}");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn long_output_is_printed_directly_when_not_a_terminal() {
+    // Paging only happens on a terminal; a pipe gets the text as-is, with or without
+    // --no-pager.
+    for args in [&["show", "rust"][..], &["show", "rust", "--no-pager"][..]] {
+        let output = gi_command(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml").as_ref(),
+            args,
+        )
+        .env("PAGER", "false")
+        .output()
+        .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("target/"));
+    }
+}
+
+#[test]
+fn translated_dockerignore_sections_pass_check() {
+    let dir = temp_path("dockerignore-check");
+    fs::create_dir_all(&dir).unwrap();
+    let config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+    let run = |args: &[&str]| gi_command(config.as_ref(), args).current_dir(&dir).output().unwrap();
+    let written = run(&["rust", "--target", "dockerignore"]);
+    assert!(written.status.success(), "{}", String::from_utf8_lossy(&written.stderr));
+
+    let output = run(&["--check", "--output", ".dockerignore"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = run(&["diff", "rust", "--output", ".dockerignore"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Rust is up to date"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn targets_write_other_ignore_files() {
+    let dir = temp_path("dockerignore");
+    fs::create_dir_all(&dir).unwrap();
+    let output = gi_command(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml").as_ref(),
+        &["rust", "--target", "dockerignore"],
+    )
+    .current_dir(&dir)
+    .output()
+    .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(dir.join(".dockerignore")).unwrap();
+    assert!(written.starts_with("# --- Rust ---\n# Written with: --target=dockerignore\n"));
+    assert!(written.contains("\n**/target/\n"), "{written}");
+    assert!(!dir.join(".gitignore").exists());
+
+    for (target, file_name) in [("npmignore", ".npmignore"), ("ignore", ".ignore")] {
+        let output = gi_command(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml").as_ref(),
+            &["rust", "--target", target],
+        )
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let written = fs::read_to_string(dir.join(file_name)).unwrap();
+        assert!(written.contains("\ntarget/\n"), "{written}");
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn import_hg_converts_rules_into_a_section() {
+    let hgignore = temp_path("hgignore");
+    fs::write(&hgignore, "syntax: glob\n*.pyc\nbuild/out\nsyntax: regexp\n^dist/\n(foo|bar)+\n")
+        .unwrap();
+    let path = temp_path("import-hg");
+    let path_arg = path.to_str().unwrap();
+
+    let output = gi(&["import-hg", hgignore.to_str().unwrap(), "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(":6: no gitignore equivalent"));
+    let written = fs::read_to_string(&path).unwrap();
+    let expected = "# --- Imported ---\n*.pyc\n**/build/out\n/dist/\n# --- end Imported ---\n";
+    assert_eq!(written, expected);
+
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&hgignore);
+}
+
+#[test]
+fn import_hg_again_replaces_the_imported_section() {
+    let hgignore = temp_path("hgignore-again");
+    let path = temp_path("import-hg-again");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "*.local\n").unwrap();
+    let import = |rules: &str| {
+        fs::write(&hgignore, rules).unwrap();
+        gi(&["import-hg", hgignore.to_str().unwrap(), "--output", path_arg])
+    };
+
+    assert!(import("syntax: glob\n*.pyc\n").status.success());
+    let output = import("syntax: glob\n*.orig\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Replaced Imported"));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "*.local\n\n# --- Imported ---\n*.orig\n# --- end Imported ---\n"
+    );
+
+    let output = gi(&["--check", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&hgignore);
+}
+
+#[test]
+fn export_svn_prints_ignore_properties() {
+    let path = temp_path("export-svn");
+    fs::write(&path, "# --- Rust ---\ntarget/\n*.log\n/dist\ndocs/_build/\n!keep.log\n").unwrap();
+
+    let output = gi(&["export-svn", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "svn:global-ignores on .\ntarget\n*.log\n\nsvn:ignore on .\ndist\n\n\
+         svn:ignore on docs\n_build\n"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no svn equivalent for `!keep.log`"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn writes_keep_the_files_line_endings_unless_told() {
+    let path = temp_path("crlf");
+    let path_arg = path.to_str().unwrap();
+    fs::write(&path, "*.log\r\n.env\r\n").unwrap();
+
+    let output = gi(&["rust", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("*.log\r\n.env\r\n\r\n# --- Rust ---\r\n"), "{written:?}");
+    assert!(!written.replace("\r\n", "").contains('\n'));
+
+    let output = gi(&["normalize", "--lf", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!fs::read_to_string(&path).unwrap().contains('\r'));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn template_names_match_in_any_case() {
+    let output = gi(&["RUST", "nODE", "--output", "-"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# --- Rust ---\n"), "{stdout}");
+    assert!(stdout.contains("# --- Node ---\n"), "{stdout}");
+}
+
+#[test]
+fn partial_names_resolve_to_the_only_match() {
+    let output = gi(&["ru", "--output", "-"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("# --- Rust ---\n"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Using Rust for `ru`"));
+}
+
+#[test]
+fn remove_deletes_named_sections() {
+    let path = temp_path("remove");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["rust", "node", "--output", path_arg]).status.success());
+    fs::write(&path, format!("*.local\n\n{}", fs::read_to_string(&path).unwrap())).unwrap();
+
+    let output = gi(&["remove", "rust", "go", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed Rust"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No go section"));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("*.local\n\n# --- Node ---\n"), "{written}");
+    assert!(!written.contains("Rust"));
+
+    // No names and no terminal: nothing to pick with.
+    assert!(!gi(&["remove", "--output", path_arg]).status.success());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn remove_keeps_lines_added_below_the_last_section() {
+    let path = temp_path("remove-appended");
+    let path_arg = path.to_str().unwrap();
+    assert!(gi(&["rust", "node", "--output", path_arg]).status.success());
+    let written = fs::read_to_string(&path).unwrap();
+    fs::write(&path, format!("{written}.env\nsecrets/\n")).unwrap();
+
+    let output = gi(&["remove", "node", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("# --- Node ---"), "{written}");
+    assert!(written.ends_with("# --- end Rust ---\n\n.env\nsecrets/\n"), "{written}");
+
+    // Written before end lines: Node runs to the end of the file, up to its last line.
+    let node = fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/raw/Node.gitignore"
+    ))
+    .unwrap();
+    fs::write(&path, format!("# --- Node ---\n{node}\n*.pem\nsecrets/\n")).unwrap();
+    let output = gi(&["remove", "node", "--output", path_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "*.pem\nsecrets/\n");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn list_filters_by_name_and_category() {
+    let output = gi(&["--list", "--filter", "US"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Rust\n");
+    let output = gi(&["--list", "--flat", "--filter", "US"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Rust\n");
+
+    let output = gi(&["--list", "--filter", "/^(node|\\+locks)$/"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Node\n+locks\n");
+
+    let output = gi(&["--list", "--category", "snippet", "--json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> =
+        entries.as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["+locks", "+macos"]);
+
+    assert!(!gi(&["--list", "--filter", "/(/"]).status.success());
+}